@@ -1,19 +1,20 @@
-#[cfg(feature = "format-bincode")]
-use bincode::deserialize_from as bincode_from_reader;
 use clap::Parser;
 use ron::de::from_reader as ron_from_reader;
 #[cfg(feature = "format-json")]
 use serde_json::from_reader as json_from_reader;
+#[cfg(feature = "format-bincode")]
+use stainless_script::program::{LegacyProgram, BINCODE_HEADER};
 use stainless_script::{
     module::ModulePath,
-    program::{Program, ProgramCollection},
-    stdlib::StdPlugin,
+    program::{ImportResolver, LoadError, Program, ProgramCollection},
+    stdlib::{number_class, StdPlugin},
     Executor,
 };
+#[cfg(feature = "format-bincode")]
+use std::io::Read;
 use std::{
     fs::File,
     path::{Path, PathBuf},
-    str::FromStr,
 };
 
 const LINUX_LIB_PATH: &str = "/usr/lib/stainless_script/";
@@ -25,6 +26,14 @@ struct Cli {
 
     #[arg(short, long, value_enum)]
     format: Option<ProgramFormat>,
+
+    /// Name of the start node to begin execution from
+    #[arg(short, long, default_value = "main")]
+    entry: String,
+
+    /// Fail on inputs of a different class than their socket instead of casting them
+    #[arg(long)]
+    strict_types: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -66,41 +75,61 @@ fn format_from_filename(file_name: &str) -> ProgramFormat {
     panic!("Failed to determine program format based on file extension, please specify program format using --format")
 }
 
-fn read_program(path: &Path, format: &ProgramFormat) -> Program {
-    let program_file = File::open(path).unwrap();
+fn read_program(path: &Path, format: &ProgramFormat) -> Result<Program, LoadError> {
+    let error = |line, column, message: String| LoadError::Deserialize {
+        path: path.display().to_string(),
+        line,
+        column,
+        message,
+    };
+    let program_file = File::open(path).map_err(|e| error(None, None, e.to_string()))?;
     match format {
-        ProgramFormat::Ron => ron_from_reader(program_file).unwrap(),
+        ProgramFormat::Ron => ron_from_reader(program_file).map_err(|e| {
+            error(
+                Some(e.position.line),
+                Some(e.position.col),
+                e.code.to_string(),
+            )
+        }),
         #[cfg(feature = "format-json")]
-        ProgramFormat::Json => json_from_reader(program_file).unwrap(),
+        ProgramFormat::Json => json_from_reader(program_file).map_err(|e| {
+            // serde_json appends the position to the message
+            let position = format!(" at line {} column {}", e.line(), e.column());
+            let message = e.to_string();
+            let message = message.strip_suffix(&position).unwrap_or(&message).into();
+            error(Some(e.line()), Some(e.column()), message)
+        }),
         #[cfg(feature = "format-bincode")]
-        ProgramFormat::Bincode => bincode_from_reader(program_file).unwrap(),
+        ProgramFormat::Bincode => {
+            bincode_from_reader(program_file).map_err(|e| error(None, None, e.to_string()))
+        }
     }
 }
 
-fn read_imports(program: &Program, programs: &mut ProgramCollection) {
-    if let Some(imports) = &program.imports {
-        for import in imports {
-            let imported_program = read_import(import);
-            read_imports(&imported_program, programs);
-            programs
-                .programs
-                .entry(ModulePath::from_str(import).unwrap())
-                .or_insert(imported_program);
-        }
+/// Files starting with [`BINCODE_HEADER`] have the current layout, older files the legacy one
+#[cfg(feature = "format-bincode")]
+fn bincode_from_reader(mut reader: impl Read) -> bincode::Result<Program> {
+    let mut bytes = vec![];
+    reader.read_to_end(&mut bytes)?;
+    match bytes.strip_prefix(BINCODE_HEADER) {
+        Some(program) => bincode::deserialize(program),
+        None => bincode::deserialize::<LegacyProgram>(&bytes).map(Program::from),
     }
 }
 
-fn read_import(name: &str) -> Program {
-    let path = ModulePath::from_str(name).unwrap();
-    let fs_path = PathBuf::from(LINUX_LIB_PATH).join(PathBuf::from_iter(path.0.iter()));
-    let mut candidates =
-        glob::glob(&format!("{}/{}.*.ssc", fs_path.to_str().unwrap(), path.1)).unwrap();
-    let program_path = candidates
-        .next()
-        .unwrap_or_else(|| panic!("Failed to find import for `{name}`"))
-        .unwrap();
-    let format = format_from_filename(program_path.file_name().unwrap().to_str().unwrap());
-    read_program(&program_path, &format)
+/// Looks up imports in the system library directory
+struct LibraryResolver;
+
+impl ImportResolver for LibraryResolver {
+    fn read_import(&mut self, path: &ModulePath) -> Result<Program, LoadError> {
+        let fs_path = PathBuf::from(LINUX_LIB_PATH).join(PathBuf::from_iter(path.0.iter()));
+        let program_path = glob::glob(&format!("{}/{}.*.ssc", fs_path.to_str().unwrap(), path.1))
+            .ok()
+            .and_then(|mut candidates| candidates.find_map(Result::ok))
+            .ok_or_else(|| LoadError::ProgramNotFound(path.clone()))?;
+        let format = format_from_filename(program_path.file_name().unwrap().to_str().unwrap());
+        read_program(&program_path, &format)
+    }
 }
 
 fn main() {
@@ -111,22 +140,57 @@ fn main() {
         format_from_filename(file_name)
     });
 
-    let main_program = read_program(&cli.program, &program_format);
+    let main_program = match read_program(&cli.program, &program_format) {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1)
+        }
+    };
 
     let mut programs = ProgramCollection::default();
 
-    read_imports(&main_program, &mut programs);
+    let main_path = ModulePath(vec![], "__main__".into());
+
+    if let Err(e) = programs.resolve_imports(main_path.clone(), &main_program, &mut LibraryResolver)
+    {
+        eprintln!("{e}");
+        std::process::exit(1)
+    }
 
-    programs
-        .programs
-        .insert(ModulePath(vec![], "__main__".into()), main_program);
+    programs.programs.insert(main_path, main_program);
+
+    for issue in programs.validate() {
+        eprintln!("Warning: {issue}");
+    }
 
     let mut executor = Executor::default();
+    executor.set_strict_types(cli.strict_types);
 
     // ADD PLUGINS HERE
-    executor.load_plugin(StdPlugin);
+    if let Err(e) = executor.load_plugin(StdPlugin) {
+        eprintln!("Failed to load plugin: {e}");
+        std::process::exit(1)
+    }
+
+    if let Err(e) = executor.load_programs(programs) {
+        eprintln!("Failed to load program: {e}");
+        std::process::exit(1)
+    }
 
-    executor.load_programs(programs);
+    if let Err(e) = executor.start_execution_from(&cli.entry, true) {
+        // Exiting skips writing of output that is still buffered
+        let _ = executor.flush_output();
+        eprintln!("Execution failed: {e}");
+        std::process::exit(1)
+    }
 
-    executor.start_execution(true);
+    // Program ends by reaching its `std.end` node. If the first value it receives is a number,
+    // it is used as the exit code.
+    let exit_code = executor
+        .final_output()
+        .and_then(|outputs| outputs.first())
+        .filter(|value| value.class() == number_class())
+        .map_or(0, |value| value.as_number() as i32);
+    std::process::exit(exit_code)
 }
@@ -1,10 +1,25 @@
 use class::Class;
 use module::ModulePath;
-use node::{AbsoluteNodeId, Node};
+use node::{AbsoluteNodeId, Node, NodeBranchId, NodeId, NodeInfo, NodeSignature};
 use object::Object;
-use program::{LoadedProgramData, Program, ProgramCollection};
-use socket::InputSocket;
-use std::{collections::HashMap, fmt::Debug, rc::Rc, str::FromStr};
+use program::{LoadError, LoadedProgramData, PatchError, Program, ProgramCollection, ProgramId};
+use serde::{Deserialize, Serialize};
+use socket::{Connection, InputSocket, InputSocketId, SocketId};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    io::{self, Write},
+    rc::Rc,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+use stdlib::{bool_class, number_class, subroutine_class, Subroutine};
+use thiserror::Error;
 
 pub mod class;
 pub mod module;
@@ -13,12 +28,187 @@ pub mod object;
 pub mod program;
 pub mod socket;
 pub mod stdlib;
+#[cfg(test)]
+mod test_utils;
 
 pub trait Plugin {
     fn classes(&self) -> HashMap<ModulePath, Class>;
 }
 
-/// Initialize with `Default::default` or `new_with_loaded` if you have already loaded data, load plugins and programs through `load_plugin` and
+/// Longest time a sleeping node waits before checking for abort requests
+const SLEEP_SLICE: Duration = Duration::from_millis(10);
+
+/// Source of waiting for nodes that pause execution. Can be replaced with `Executor::set_clock`,
+/// for example to not block in tests.
+pub trait Clock: Debug {
+    fn sleep(&self, duration: Duration);
+}
+
+/// Waits using `std::thread::sleep`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn sleep(&self, duration: Duration) {
+        std::thread::sleep(duration)
+    }
+}
+
+/// Pseudo-random number generator for nodes (SplitMix64). Seeded from the current time unless a
+/// seed is set with `Executor::set_seed`, which makes runs reproducible.
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl Default for Rng {
+    fn default() -> Self {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
+        Self(now.as_nanos() as u64)
+    }
+}
+
+/// Access to a variable, reported to the tracer set with `Executor::set_variable_tracer`
+#[derive(Debug, Clone)]
+pub enum VarEvent {
+    /// `value` is `None` if the variable is not set
+    Read {
+        name: String,
+        value: Option<Rc<dyn Object>>,
+    },
+    Write {
+        name: String,
+        value: Rc<dyn Object>,
+    },
+}
+
+/// Callback for variable access, see `Executor::set_variable_tracer`
+pub type VariableTraceFn = dyn FnMut(VarEvent);
+
+/// Behind `RefCell` since variables are read through shared references to the executor
+#[derive(Clone)]
+struct VariableTracer(Rc<RefCell<Box<VariableTraceFn>>>);
+
+impl VariableTracer {
+    fn trace(&self, event: VarEvent) {
+        (self.0.borrow_mut())(event)
+    }
+}
+
+impl Debug for VariableTracer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("VariableTracer")
+    }
+}
+
+/// When text printed by scripts is passed on to the output writer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputBuffering {
+    /// Write text as soon as it is printed
+    Unbuffered,
+    /// Write text once a line is complete
+    #[default]
+    LineBuffered,
+    /// Write text once at least this many bytes are collected
+    BlockBuffered(usize),
+}
+
+/// Destination of text printed by scripts. Stdout is used if no writer is set. Text that is still
+/// buffered is written when the output is dropped.
+#[derive(Default)]
+struct Output {
+    writer: Option<Box<dyn Write>>,
+    buffering: OutputBuffering,
+    buffer: Vec<u8>,
+}
+
+impl Output {
+    fn write(&mut self, text: &str) -> io::Result<()> {
+        self.buffer.extend_from_slice(text.as_bytes());
+        let ready = match self.buffering {
+            OutputBuffering::Unbuffered => self.buffer.len(),
+            OutputBuffering::LineBuffered => self
+                .buffer
+                .iter()
+                .rposition(|&byte| byte == b'\n')
+                .map_or(0, |idx| idx + 1),
+            OutputBuffering::BlockBuffered(size) if self.buffer.len() >= size => self.buffer.len(),
+            OutputBuffering::BlockBuffered(_) => 0,
+        };
+        if ready > 0 {
+            self.write_out(ready)?;
+        }
+        Ok(())
+    }
+
+    /// Write everything that is buffered
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_out(self.buffer.len())
+    }
+
+    fn write_out(&mut self, amount: usize) -> io::Result<()> {
+        let bytes: Vec<u8> = self.buffer.drain(..amount).collect();
+        match &mut self.writer {
+            Some(writer) => {
+                writer.write_all(&bytes)?;
+                writer.flush()
+            }
+            None => {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(&bytes)?;
+                stdout.flush()
+            }
+        }
+    }
+}
+
+impl Debug for Output {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Output")
+            .field("buffering", &self.buffering)
+            .field("buffered", &self.buffer.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Function of the host program that can be called from scripts through `std.native` nodes.
+/// Receives the node's inputs and returns its outputs.
+pub type NativeFunction = dyn Fn(Vec<Rc<dyn Object>>) -> Vec<Rc<dyn Object>>;
+
+/// Native functions registered in an executor, by name
+#[derive(Clone, Default)]
+struct NativeFunctions(HashMap<String, Rc<NativeFunction>>);
+
+impl Debug for NativeFunctions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.0.keys()).finish()
+    }
+}
+
+impl<P: Plugin + ?Sized> Plugin for Box<P> {
+    fn classes(&self) -> HashMap<ModulePath, Class> {
+        (**self).classes()
+    }
+}
+
+/// Initialize with `Default::default`, `new_with_loaded` if you have already loaded data, or
+/// `ExecutorBuilder`. Load plugins and programs through `load_plugin` and
 /// `load_program`, start execution with `start_execution`, execute step-by-step with `execute_step` (will advance automatically)
 #[derive(Debug, Clone, Default)]
 pub struct Executor {
@@ -27,6 +217,24 @@ pub struct Executor {
     auto_execution: bool,
     stop_point: Option<AbsoluteNodeId>,
     variables: HashMap<String, Rc<dyn Object>>,
+    final_output: Option<Vec<Rc<dyn Object>>>,
+    /// Stack depths to which subroutines run by `run_subroutine_to_completion` return
+    nested_calls: Vec<usize>,
+    nested_return: Option<Vec<Rc<dyn Object>>>,
+    last_yield: Option<Rc<dyn Object>>,
+    /// Total execution time and amount of executions per class name, if profiling is enabled
+    profile: Option<HashMap<String, (Duration, u64)>>,
+    /// Set through an `AbortHandle` to stop automatic execution
+    abort_requested: Arc<AtomicBool>,
+    /// `SystemClock` is used if not set
+    clock: Option<Rc<dyn Clock>>,
+    natives: NativeFunctions,
+    /// Shared between clones of the executor, so buffered text is written once
+    output: Rc<RefCell<Output>>,
+    /// Whether inputs of a different class than their socket are an error instead of being cast
+    strict_types: bool,
+    rng: Rng,
+    variable_tracer: Option<VariableTracer>,
 }
 
 impl Executor {
@@ -37,16 +245,19 @@ impl Executor {
 
     fn finish_subroutine(&mut self, return_values: Vec<Rc<dyn Object>>) {
         self.node_stack.pop();
-        self.set_node_outputs(return_values);
+        if self.nested_calls.last() == Some(&self.node_stack.len()) {
+            self.nested_return = Some(return_values);
+        } else if self.node_stack.is_empty() {
+            self.final_output = Some(return_values);
+        } else {
+            self.set_node_outputs(return_values);
+        }
     }
 
-    fn get_node_inputs(&self) -> Vec<Rc<dyn Object>> {
+    /// Values of inputs of the current node. `None` for inputs that have no value.
+    fn get_node_inputs(&self) -> Vec<Option<Rc<dyn Object>>> {
         if let Some(current_node) = self.current_node() {
-            self.loaded
-                .get_inputs(current_node)
-                .into_iter()
-                .collect::<Option<Vec<Rc<dyn Object>>>>()
-                .unwrap()
+            self.loaded.get_inputs(current_node)
         } else {
             vec![]
         }
@@ -58,14 +269,99 @@ impl Executor {
         }
     }
 
+    fn get_node_input(&self, idx: usize) -> Option<Rc<dyn Object>> {
+        let current_node = self.current_node()?;
+        self.loaded.get_inputs(current_node).get(idx).cloned()?
+    }
+
     fn current_node(&self) -> Option<&AbsoluteNodeId> {
         self.node_stack.last()?.as_ref()
     }
 
-    pub fn execute_step(&mut self) {
-        let node_id = self.current_node();
-        let node = self.get_node_by_id(node_id);
-        let mut inputs = node.inputs();
+    /// Execute current node and advance to the next one. If the node fails, execution stays at
+    /// that node. Buffered output is flushed once the program ends.
+    pub fn execute_step(&mut self) -> Result<(), ExecutionError> {
+        let branch = self.execute_current()?;
+        self.advance(branch);
+        if self.node_stack.is_empty() {
+            self.flush_output()
+                .map_err(|e| ExecutionError::Output(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Like `execute_step`, but a subroutine call is executed along with the whole subroutine,
+    /// stopping at the node after the call
+    pub fn step_over(&mut self) -> Result<(), ExecutionError> {
+        let node = self.get_node_by_id(self.current_node());
+        if node.class() != subroutine_class() {
+            return self.execute_step();
+        }
+        let subroutine: Subroutine = node.current_variant().parse().unwrap();
+        let inputs = self.get_input_sockets(node.as_ref());
+        let input_values = ExecutionContext::new(self, inputs).get_inputs()?;
+        let return_values =
+            self.run_subroutine_to_completion(subroutine.start().clone(), input_values)?;
+        self.set_node_outputs(return_values);
+        self.advance(0);
+        Ok(())
+    }
+
+    /// Run a subroutine until it returns, without advancing the node that called it. Returns
+    /// whatever the end node of the subroutine receives. If execution is aborted, the frames of
+    /// the subroutine are dropped, so the calling node runs again once execution is resumed.
+    pub fn run_subroutine_to_completion(
+        &mut self,
+        start: AbsoluteNodeId,
+        input_values: Vec<Rc<dyn Object>>,
+    ) -> Result<Vec<Rc<dyn Object>>, ExecutionError> {
+        let depth = self.node_stack.len();
+        self.nested_calls.push(depth);
+        self.execute_subroutine(start, input_values);
+        self.advance(0);
+        while self.node_stack.len() > depth {
+            if self.abort_requested.swap(false, Ordering::Relaxed) {
+                self.nested_calls.pop();
+                self.node_stack.truncate(depth);
+                self.auto_execution = false;
+                return Err(ExecutionError::Aborted);
+            }
+            let branch = match self.execute_current() {
+                Ok(branch) => branch,
+                Err(e) => {
+                    self.nested_calls.pop();
+                    return Err(e);
+                }
+            };
+            if self.node_stack.len() > depth {
+                self.advance(branch);
+            }
+        }
+        self.nested_calls.pop();
+        Ok(self.nested_return.take().unwrap_or_default())
+    }
+
+    /// Execute current node without advancing. Returns the branch it chose.
+    fn execute_current(&mut self) -> Result<usize, ExecutionError> {
+        let node = self.get_node_by_id(self.current_node());
+        let inputs = self.get_input_sockets(node.as_ref());
+        if self.profile.is_none() {
+            return node.execute(&mut ExecutionContext::new(self, inputs));
+        }
+        let start = Instant::now();
+        let result = node.execute(&mut ExecutionContext::new(self, inputs));
+        let elapsed = start.elapsed();
+        if let Some(profile) = &mut self.profile {
+            let entry = profile.entry(node.class().name).or_default();
+            entry.0 += elapsed;
+            entry.1 += 1;
+        }
+        result
+    }
+
+    /// Input sockets of a node. Subroutine calls take inputs of their subroutine's start node.
+    fn get_input_sockets(&self, node: &dyn Node) -> Vec<InputSocket> {
+        let inputs = node.inputs();
         if let Some(input) = inputs.get(0) {
             if input.class.name.starts_with("subroutine_input@") {
                 let id = AbsoluteNodeId::from_str(
@@ -77,16 +373,17 @@ impl Executor {
                 )
                 .unwrap();
                 let real_node = self.get_node_by_id(Some(&id));
-                inputs = real_node
+                return real_node
                     .outputs()
                     .into_iter()
-                    .map(|os| InputSocket { class: os.class })
-                    .collect()
+                    .map(|os| InputSocket {
+                        class: os.class,
+                        default: None,
+                    })
+                    .collect();
             }
         }
-        let mut context = ExecutionContext::new(self, inputs);
-        let branch = node.execute(&mut context);
-        self.advance(branch);
+        inputs
     }
 
     fn get_node_by_id(&self, node_id: Option<&AbsoluteNodeId>) -> Rc<dyn Node> {
@@ -113,47 +410,165 @@ impl Executor {
         self.loaded.get_next_node(current, branch)
     }
 
-    pub fn load_program(&mut self, program: Program, path: ModulePath) {
+    pub fn load_program(&mut self, program: Program, path: ModulePath) -> Result<(), LoadError> {
         self.loaded.load_program(&path, &program)
     }
 
-    pub fn load_programs(&mut self, programs: ProgramCollection) {
+    pub fn load_programs(&mut self, programs: ProgramCollection) -> Result<(), LoadError> {
         self.loaded.load_programs(&programs)
     }
 
-    pub fn load_plugin(&mut self, plugin: impl Plugin) {
+    pub fn load_plugin(&mut self, plugin: impl Plugin) -> Result<(), LoadError> {
         self.loaded.load_plugin(plugin)
     }
 
-    pub fn start_execution(&mut self, auto: bool) {
-        self.auto_execution = auto;
+    /// Place a node or replace an already placed one. Class of the node has to be loaded and have a
+    /// node at `idx`.
+    pub fn upsert_node(
+        &mut self,
+        node_id: AbsoluteNodeId,
+        node: NodeInfo,
+    ) -> Result<(), PatchError> {
+        self.loaded.upsert_node(&node_id, node)?;
+        Ok(())
+    }
+
+    pub fn remove_node(&mut self, node_id: &AbsoluteNodeId) {
+        if let Some(program) = self.loaded.programs.get_mut(&node_id.0) {
+            program.remove_node(node_id.1)
+        }
+    }
+
+    pub fn set_branch_edge(
+        &mut self,
+        program_id: &ProgramId,
+        branch: NodeBranchId,
+        next: NodeId,
+    ) -> Result<(), PatchError> {
+        self.loaded.set_branch_edge(program_id, branch, next)
+    }
+
+    pub fn remove_branch_edge(&mut self, program_id: &ProgramId, branch: &NodeBranchId) {
+        self.loaded.remove_branch_edge(program_id, branch);
+    }
+
+    /// Set the node where unconnected branches of `node` continue
+    pub fn set_fallback_edge(
+        &mut self,
+        program_id: &ProgramId,
+        node: NodeId,
+        next: NodeId,
+    ) -> Result<(), PatchError> {
+        self.loaded.set_fallback_edge(program_id, node, next)
+    }
+
+    pub fn remove_fallback_edge(&mut self, program_id: &ProgramId, node: NodeId) {
+        self.loaded.remove_fallback_edge(program_id, node);
+    }
+
+    pub fn add_connection(
+        &mut self,
+        program_id: &ProgramId,
+        connection: Connection,
+    ) -> Result<(), PatchError> {
+        self.loaded.add_connection(program_id, connection)
+    }
+
+    pub fn remove_connection(&mut self, program_id: &ProgramId, connection: &Connection) {
+        self.loaded.remove_connection(program_id, connection)
+    }
+
+    pub fn get_const_input(&self, program_id: &ProgramId, socket: InputSocketId) -> Option<&str> {
+        self.loaded.get_const_input(program_id, socket)
+    }
+
+    pub fn set_const_input(
+        &mut self,
+        program_id: &ProgramId,
+        socket: InputSocketId,
+        value: String,
+    ) -> Result<(), PatchError> {
+        self.loaded.set_const_input(program_id, socket, value)
+    }
+
+    /// Const value of an input of a node, if it has one
+    pub fn node_const_input(&self, node: &AbsoluteNodeId, idx: usize) -> Option<&str> {
+        self.get_const_input(&node.0, InputSocketId(SocketId(node.1, idx)))
+    }
+
+    /// Set the const value of an input of a node. Takes effect the next time the node reads its
+    /// inputs.
+    pub fn set_node_const_input(
+        &mut self,
+        node: &AbsoluteNodeId,
+        idx: usize,
+        value: String,
+    ) -> Result<(), PatchError> {
+        self.set_const_input(&node.0, InputSocketId(SocketId(node.1, idx)), value)
+    }
+
+    pub fn start_execution(&mut self, auto: bool) -> Result<(), ExecutionError> {
+        self.start_execution_from("main", auto)
+    }
+
+    /// Start execution from a start node of the main program with a given name, so that a
+    /// program can have several entry points
+    pub fn start_execution_from(&mut self, entry: &str, auto: bool) -> Result<(), ExecutionError> {
         let start_node = self
             .loaded
-            .get_start_node(ModulePath(vec![], "__main__".into()), "main");
-        self.node_stack.push(Some(start_node.unwrap()));
-        self.execution_loop();
+            .get_start_node(ModulePath(vec![], "__main__".into()), entry)
+            .ok_or_else(|| ExecutionError::MissingEntryPoint(entry.into()))?;
+        self.auto_execution = auto;
+        self.node_stack.push(Some(start_node));
+        self.execution_loop()
     }
 
-    fn execution_loop(&mut self) {
+    /// Execute nodes while automatic execution is on. Stops on the first error, leaving the
+    /// failed node as the current one.
+    fn execution_loop(&mut self) -> Result<(), ExecutionError> {
         while !self.node_stack.is_empty() && self.auto_execution {
-            self.execute_step();
+            if self.abort_requested.swap(false, Ordering::Relaxed) {
+                self.auto_execution = false;
+                return Err(ExecutionError::Aborted);
+            }
+            if let Err(e) = self.execute_step() {
+                self.auto_execution = false;
+                return Err(e);
+            }
             if let Some(node) = &self.stop_point {
                 if self.current_node() == Some(node) {
                     self.auto_execution = false
                 }
             }
         }
+        Ok(())
     }
 
-    pub fn resume_auto(&mut self) {
+    pub fn resume_auto(&mut self) -> Result<(), ExecutionError> {
         self.auto_execution = true;
-        self.execution_loop();
+        self.execution_loop()
     }
 
-    pub fn resume_until(&mut self, node: AbsoluteNodeId) {
+    pub fn resume_until(&mut self, node: AbsoluteNodeId) -> Result<(), ExecutionError> {
         self.stop_point = Some(node);
         self.auto_execution = true;
-        self.execution_loop();
+        self.execution_loop()
+    }
+
+    /// Rewind to the state before the first run, keeping loaded programs and classes, so that
+    /// programs can be run again without reloading them. Clears the execution stack, variables,
+    /// stop point and values passed through connections.
+    pub fn reset(&mut self) {
+        self.node_stack.clear();
+        self.auto_execution = false;
+        self.stop_point = None;
+        self.variables.clear();
+        self.final_output = None;
+        self.nested_calls.clear();
+        self.nested_return = None;
+        self.last_yield = None;
+        self.abort_requested.store(false, Ordering::Relaxed);
+        self.loaded.clear_outputs();
     }
 
     pub fn new_with_loaded(loaded: LoadedProgramData) -> Self {
@@ -163,15 +578,356 @@ impl Executor {
             auto_execution: bool::default(),
             stop_point: None,
             variables: HashMap::default(),
+            final_output: None,
+            nested_calls: Vec::default(),
+            nested_return: None,
+            last_yield: None,
+            profile: None,
+            abort_requested: Arc::default(),
+            clock: None,
+            natives: NativeFunctions::default(),
+            output: Rc::default(),
+            strict_types: false,
+            rng: Rng::default(),
+            variable_tracer: None,
+        }
+    }
+
+    /// Stop automatic execution and pass a value to the host. Execution continues from the next
+    /// node when resumed.
+    pub fn yield_value(&mut self, value: Rc<dyn Object>) {
+        self.last_yield = Some(value);
+        self.auto_execution = false;
+    }
+
+    /// Handle to stop automatic execution from another thread. Execution stops before the next
+    /// node with `ExecutionError::Aborted` and can be resumed afterwards.
+    pub fn abort_handle(&self) -> AbortHandle {
+        AbortHandle(Arc::clone(&self.abort_requested))
+    }
+
+    /// Replace the clock used by nodes that wait
+    pub fn set_clock(&mut self, clock: Rc<dyn Clock>) {
+        self.clock = Some(clock)
+    }
+
+    /// Write text printed by scripts to a writer instead of stdout. Text that is buffered but not
+    /// written yet goes to the new writer.
+    pub fn set_output(&mut self, writer: Box<dyn Write>) {
+        self.output.borrow_mut().writer = Some(writer)
+    }
+
+    pub fn set_output_buffering(&mut self, buffering: OutputBuffering) {
+        self.output.borrow_mut().buffering = buffering
+    }
+
+    /// Write all buffered output and flush the writer
+    pub fn flush_output(&mut self) -> io::Result<()> {
+        self.output.borrow_mut().flush()
+    }
+
+    /// In strict mode, an input of a different class than its socket fails with
+    /// `ExecutionError::SocketTypeMismatch` instead of being cast. Sockets of class `any` still
+    /// accept everything.
+    pub fn set_strict_types(&mut self, strict: bool) {
+        self.strict_types = strict
+    }
+
+    /// Seed the random number generator used by nodes, so that they produce the same values on
+    /// every run
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng(seed)
+    }
+
+    /// Make a host function callable from scripts under a name. Replaces a function previously
+    /// registered under the same name.
+    pub fn register_native(&mut self, name: &str, function: Box<NativeFunction>) {
+        self.natives.0.insert(name.into(), function.into());
+    }
+
+    /// Wait for a duration in short slices, checking for abort requests between them
+    fn sleep(&mut self, duration: Duration) -> Result<(), ExecutionError> {
+        let clock = self.clock.clone().unwrap_or_else(|| Rc::new(SystemClock));
+        let mut remaining = duration;
+        while !remaining.is_zero() {
+            if self.abort_requested.swap(false, Ordering::Relaxed) {
+                self.auto_execution = false;
+                return Err(ExecutionError::Aborted);
+            }
+            let slice = remaining.min(SLEEP_SLICE);
+            clock.sleep(slice);
+            remaining -= slice;
+        }
+        Ok(())
+    }
+
+    /// Enable or disable measuring of node execution time. Disabling discards collected data.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        if !enabled {
+            self.profile = None
+        } else if self.profile.is_none() {
+            self.profile = Some(HashMap::default())
         }
     }
 
+    /// Total execution time and amount of executions of nodes of each class, sorted by class
+    /// name. Empty if profiling is disabled.
+    pub fn profile_report(&self) -> Vec<(String, Duration, u64)> {
+        let mut report: Vec<_> = self
+            .profile
+            .iter()
+            .flatten()
+            .map(|(class, (time, calls))| (class.clone(), *time, *calls))
+            .collect();
+        report.sort_by(|lhs, rhs| lhs.0.cmp(&rhs.0));
+        report
+    }
+
+    /// Value passed by the last executed `std.yield` node
+    pub fn last_yield(&self) -> Option<&Rc<dyn Object>> {
+        self.last_yield.as_ref()
+    }
+
     pub fn set_variable(&mut self, name: &str, val: Rc<dyn Object>) {
+        if let Some(tracer) = &self.variable_tracer {
+            tracer.trace(VarEvent::Write {
+                name: name.into(),
+                value: Rc::clone(&val),
+            });
+        }
         self.variables.insert(name.to_string(), val);
     }
 
     pub fn get_variable(&self, name: &str) -> Option<Rc<dyn Object>> {
-        Some(Rc::clone(self.variables.get(name)?))
+        let value = self.variables.get(name).cloned();
+        if let Some(tracer) = &self.variable_tracer {
+            tracer.trace(VarEvent::Read {
+                name: name.into(),
+                value: value.clone(),
+            });
+        }
+        value
+    }
+
+    /// Call a function on every read and write of a variable, or stop doing so with `None`.
+    /// Snapshots don't count as access.
+    pub fn set_variable_tracer(&mut self, tracer: Option<Box<VariableTraceFn>>) {
+        self.variable_tracer = tracer.map(|tracer| VariableTracer(Rc::new(RefCell::new(tracer))))
+    }
+
+    /// Save variables in text form, so they can be persisted and later restored with `restore`
+    pub fn snapshot(&self) -> Snapshot {
+        let variables = self
+            .variables
+            .iter()
+            .map(|(name, value)| {
+                let (path, data) = value.serialize_with_class();
+                (name.clone(), (self.resolve_class_path(path), data))
+            })
+            .collect();
+        Snapshot { variables }
+    }
+
+    /// Set variables saved by `snapshot`. Each value is reconstructed through `obj_from_str` of
+    /// its class. Nothing is set if any of the values fails to restore.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), SnapshotError> {
+        let variables = snapshot
+            .variables
+            .iter()
+            .map(|(name, (path, data))| {
+                let class = self
+                    .loaded
+                    .get_class(path.clone())
+                    .ok_or_else(|| SnapshotError::UnknownClass(path.clone()))?;
+                let obj_from_str = class
+                    .obj_from_str
+                    .ok_or_else(|| SnapshotError::NotConstructible(path.clone()))?;
+                let value = obj_from_str(data).map_err(|e| SnapshotError::Parse {
+                    variable: name.clone(),
+                    error: e.to_string(),
+                })?;
+                Ok((name.clone(), value))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        self.variables.extend(variables);
+        Ok(())
+    }
+
+    /// Find full path of a loaded class if the path has no module prefix
+    fn resolve_class_path(&self, path: ModulePath) -> ModulePath {
+        if !path.0.is_empty() {
+            return path;
+        }
+        self.loaded
+            .modules
+            .classes()
+            .into_iter()
+            .find(|(_, class)| class.name == path.1)
+            .map_or(path, |(full_path, _)| full_path)
+    }
+
+    /// Interface of a loaded node: its sockets, branches and variants
+    pub fn node_signature(&self, node_id: &AbsoluteNodeId) -> Option<NodeSignature> {
+        let node = self.loaded.get_node(node_id)?;
+        Some(NodeSignature::of(node.as_ref()))
+    }
+
+    /// Values received by the end node of the top-level program, available once the program
+    /// has finished executing
+    pub fn final_output(&self) -> Option<&[Rc<dyn Object>]> {
+        self.final_output.as_deref()
+    }
+
+    /// Node that is going to be executed next
+    pub fn current_node_id(&self) -> Option<AbsoluteNodeId> {
+        self.current_node().cloned()
+    }
+
+    /// Frames of the execution stack, from the currently executed node to the outermost caller
+    pub fn stack_trace(&self) -> Vec<AbsoluteNodeId> {
+        self.node_stack.iter().rev().flatten().cloned().collect()
+    }
+
+    /// How many frames are on the execution stack. Each subroutine call adds a frame.
+    pub fn stack_depth(&self) -> usize {
+        self.node_stack.len()
+    }
+
+    /// The node that called the subroutine currently being executed. `None` at top level.
+    pub fn caller_node(&self) -> Option<&AbsoluteNodeId> {
+        self.node_stack.iter().rev().nth(1)?.as_ref()
+    }
+}
+
+/// Stops automatic execution of the executor it was made by, see `Executor::abort_handle`
+#[derive(Debug, Clone)]
+pub struct AbortHandle(Arc<AtomicBool>);
+
+impl AbortHandle {
+    pub fn abort(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+}
+
+/// Configures an executor in one chain instead of calling setters one by one. Plugins are loaded
+/// before programs when `build` is called, in the order they were added.
+#[derive(Default)]
+pub struct ExecutorBuilder {
+    loaded: LoadedProgramData,
+    plugins: Vec<Box<dyn Plugin>>,
+    programs: ProgramCollection,
+    variables: Vec<(String, Rc<dyn Object>)>,
+    stop_point: Option<AbsoluteNodeId>,
+    profiling: bool,
+    clock: Option<Rc<dyn Clock>>,
+    natives: Vec<(String, Box<NativeFunction>)>,
+    output: Option<Box<dyn Write>>,
+    output_buffering: OutputBuffering,
+    strict_types: bool,
+    seed: Option<u64>,
+}
+
+impl ExecutorBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from already loaded data, like `Executor::new_with_loaded`
+    pub fn with_loaded(mut self, loaded: LoadedProgramData) -> Self {
+        self.loaded = loaded;
+        self
+    }
+
+    pub fn plugin(mut self, plugin: impl Plugin + 'static) -> Self {
+        self.plugins.push(Box::new(plugin));
+        self
+    }
+
+    pub fn program(mut self, program: Program, path: ModulePath) -> Self {
+        self.programs.programs.insert(path, program);
+        self
+    }
+
+    pub fn programs(mut self, programs: ProgramCollection) -> Self {
+        self.programs.programs.extend(programs.programs);
+        self
+    }
+
+    pub fn variable(mut self, name: &str, value: Rc<dyn Object>) -> Self {
+        self.variables.push((name.into(), value));
+        self
+    }
+
+    /// Node at which automatic execution stops, same as the one set by `Executor::resume_until`
+    pub fn stop_point(mut self, node: AbsoluteNodeId) -> Self {
+        self.stop_point = Some(node);
+        self
+    }
+
+    pub fn profiling(mut self, enabled: bool) -> Self {
+        self.profiling = enabled;
+        self
+    }
+
+    pub fn clock(mut self, clock: Rc<dyn Clock>) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    /// Writer for text printed by scripts, see `Executor::set_output`
+    pub fn output(mut self, writer: Box<dyn Write>) -> Self {
+        self.output = Some(writer);
+        self
+    }
+
+    pub fn output_buffering(mut self, buffering: OutputBuffering) -> Self {
+        self.output_buffering = buffering;
+        self
+    }
+
+    /// See `Executor::set_strict_types`
+    pub fn strict_types(mut self, strict: bool) -> Self {
+        self.strict_types = strict;
+        self
+    }
+
+    /// See `Executor::set_seed`
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Register a host function, see `Executor::register_native`
+    pub fn native(mut self, name: &str, function: Box<NativeFunction>) -> Self {
+        self.natives.push((name.into(), function));
+        self
+    }
+
+    /// Load plugins and programs and make the executor
+    pub fn build(self) -> Result<Executor, LoadError> {
+        let mut executor = Executor::new_with_loaded(self.loaded);
+        for plugin in self.plugins {
+            executor.load_plugin(plugin)?;
+        }
+        executor.load_programs(self.programs)?;
+        for (name, value) in self.variables {
+            executor.set_variable(&name, value);
+        }
+        executor.stop_point = self.stop_point;
+        executor.set_profiling(self.profiling);
+        executor.clock = self.clock;
+        for (name, function) in self.natives {
+            executor.register_native(&name, function);
+        }
+        if let Some(writer) = self.output {
+            executor.set_output(writer);
+        }
+        executor.set_output_buffering(self.output_buffering);
+        executor.set_strict_types(self.strict_types);
+        if let Some(seed) = self.seed {
+            executor.set_seed(seed);
+        }
+        Ok(executor)
     }
 }
 
@@ -196,25 +952,111 @@ impl<'a> ExecutionContext<'a> {
         self.executor.finish_subroutine(return_values);
     }
 
-    pub fn get_inputs(&self) -> Vec<Rc<dyn Object>> {
-        self.executor
-            .get_node_inputs()
+    /// All inputs, cast to classes of their sockets. Fails with `MissingInput` if any of the
+    /// sockets has no value and with `SocketTypeMismatch` if an input can't be cast.
+    pub fn get_inputs(&self) -> Result<Vec<Rc<dyn Object>>, ExecutionError> {
+        let values = self.executor.get_node_inputs();
+        if values.len() < self.inputs.len() || values.iter().any(Option::is_none) {
+            return Err(self.missing_input());
+        }
+        values
             .into_iter()
+            .flatten()
             .zip(self.inputs.iter())
-            .map(|(iv, ec)| {
+            .enumerate()
+            .map(|(idx, (iv, ec))| {
                 if iv.class() != ec.class && ec.class.name != "any" {
-                    iv.cast_to(&ec.class)
+                    self.cast_input(idx, iv, &ec.class)
                 } else {
-                    iv
+                    Ok(iv)
                 }
             })
             .collect()
     }
 
+    /// Input at an index, cast to the class of its socket
+    pub fn get_object(&self, idx: usize) -> Result<Rc<dyn Object>, ExecutionError> {
+        let value = self
+            .executor
+            .get_node_input(idx)
+            .ok_or_else(|| self.missing_input())?;
+        match self.inputs.get(idx) {
+            Some(socket) if value.class() != socket.class && socket.class.name != "any" => {
+                self.cast_input(idx, value, &socket.class)
+            }
+            _ => Ok(value),
+        }
+    }
+
+    /// Input at an index as a number. Objects of other classes are cast to a number.
+    pub fn get_number(&self, idx: usize) -> Result<f64, ExecutionError> {
+        let value = self.get_object(idx)?;
+        let number = number_class();
+        if value.class() == number {
+            Ok(value.as_number())
+        } else {
+            Ok(self.cast_input(idx, value, &number)?.as_number())
+        }
+    }
+
+    /// Input at an index as a bool. Objects of other classes are cast to a bool.
+    pub fn get_bool(&self, idx: usize) -> Result<bool, ExecutionError> {
+        let value = self.get_object(idx)?;
+        let bool = bool_class();
+        if value.class() == bool {
+            Ok(value.as_bool())
+        } else {
+            Ok(self.cast_input(idx, value, &bool)?.as_bool())
+        }
+    }
+
+    /// String representation of the input at an index. Any object can be taken as a string.
+    pub fn get_string(&self, idx: usize) -> Result<String, ExecutionError> {
+        Ok(self.get_object(idx)?.as_string())
+    }
+
+    fn cast_input(
+        &self,
+        idx: usize,
+        value: Rc<dyn Object>,
+        class: &Class,
+    ) -> Result<Rc<dyn Object>, ExecutionError> {
+        let cast = if self.executor.strict_types {
+            None
+        } else {
+            value.try_cast_to(class)
+        };
+        cast.ok_or_else(|| ExecutionError::SocketTypeMismatch {
+            node: self.current_node_id(),
+            socket_index: idx,
+            expected: class.name.clone(),
+            found: value.class().name,
+        })
+    }
+
+    fn missing_input(&self) -> ExecutionError {
+        let node = self.current_node_id();
+        ExecutionError::MissingInput {
+            expected: self.inputs.len(),
+            got: self
+                .executor
+                .loaded
+                .get_inputs(&node)
+                .iter()
+                .flatten()
+                .count(),
+            node,
+        }
+    }
+
     pub fn set_outputs(&mut self, values: Vec<Rc<dyn Object>>) {
         self.executor.set_node_outputs(values)
     }
 
+    pub fn yield_value(&mut self, value: Rc<dyn Object>) {
+        self.executor.yield_value(value)
+    }
+
     pub fn set_variable(&mut self, name: &str, val: Rc<dyn Object>) {
         self.executor.set_variable(name, val)
     }
@@ -222,4 +1064,717 @@ impl<'a> ExecutionContext<'a> {
     pub fn get_variable(&self, name: &str) -> Option<Rc<dyn Object>> {
         self.executor.get_variable(name)
     }
+
+    /// Names and values of all variables. Every variable counts as read.
+    pub fn variables(&self) -> Vec<(String, Rc<dyn Object>)> {
+        self.executor
+            .variables
+            .keys()
+            .filter_map(|name| Some((name.clone(), self.executor.get_variable(name)?)))
+            .collect()
+    }
+
+    pub fn get_class(&self, path: &ModulePath) -> Option<Class> {
+        self.executor.loaded.get_class(path.clone()).cloned()
+    }
+
+    /// Loaded class with a name, wherever it is loaded. Objects only know the name of their class.
+    pub fn find_class(&self, name: &str) -> Option<Class> {
+        let path = self
+            .executor
+            .resolve_class_path(ModulePath(vec![], name.into()));
+        self.get_class(&path)
+    }
+
+    pub fn stack_depth(&self) -> usize {
+        self.executor.stack_depth()
+    }
+
+    /// Pause execution. Fails with `ExecutionError::Aborted` if an abort is requested while
+    /// waiting.
+    pub fn sleep(&mut self, duration: Duration) -> Result<(), ExecutionError> {
+        self.executor.sleep(duration)
+    }
+
+    /// Next value of the executor's random number generator, see `Executor::set_seed`
+    pub fn random_u64(&mut self) -> u64 {
+        self.executor.rng.next_u64()
+    }
+
+    /// Run a subroutine to its end before continuing with the current node, returning whatever its
+    /// end node receives. See `Executor::run_subroutine_to_completion`.
+    pub fn run_subroutine(
+        &mut self,
+        start: AbsoluteNodeId,
+        input_values: Vec<Rc<dyn Object>>,
+    ) -> Result<Vec<Rc<dyn Object>>, ExecutionError> {
+        self.executor
+            .run_subroutine_to_completion(start, input_values)
+    }
+
+    /// Like `run_subroutine`, but if the subroutine fails, execution returns to the current node
+    /// instead of staying at the node that failed, so the error can be handled by the current node
+    pub fn try_run_subroutine(
+        &mut self,
+        start: AbsoluteNodeId,
+        input_values: Vec<Rc<dyn Object>>,
+    ) -> Result<Vec<Rc<dyn Object>>, ExecutionError> {
+        let depth = self.executor.node_stack.len();
+        let result = self
+            .executor
+            .run_subroutine_to_completion(start, input_values);
+        self.executor.node_stack.truncate(depth);
+        result
+    }
+
+    /// Print text to the output of the executor
+    pub fn write_output(&mut self, text: &str) -> Result<(), ExecutionError> {
+        self.executor
+            .output
+            .borrow_mut()
+            .write(text)
+            .map_err(|e| ExecutionError::Output(e.to_string()))
+    }
+
+    /// Call a native function registered in the executor. `None` if there is no function with
+    /// that name.
+    pub fn call_native(
+        &self,
+        name: &str,
+        args: Vec<Rc<dyn Object>>,
+    ) -> Option<Vec<Rc<dyn Object>>> {
+        let function = Rc::clone(self.executor.natives.0.get(name)?);
+        Some(function(args))
+    }
+
+    pub fn caller_node(&self) -> Option<AbsoluteNodeId> {
+        self.executor.caller_node().cloned()
+    }
+
+    /// Id of the node being executed
+    pub fn current_node_id(&self) -> AbsoluteNodeId {
+        self.executor.current_node_id().unwrap()
+    }
+
+    /// Ids of nodes on the execution stack, from current node to the outermost caller
+    pub fn stack_trace(&self) -> Vec<AbsoluteNodeId> {
+        self.executor.stack_trace()
+    }
+
+    pub fn get_node(&self, node_id: &AbsoluteNodeId) -> Option<Rc<dyn Node>> {
+        self.executor.loaded.get_node(node_id)
+    }
+
+    /// Id of the start node with a name in a loaded program
+    pub fn get_start_node(&self, program: &ProgramId, name: &str) -> Option<AbsoluteNodeId> {
+        self.executor.loaded.get_start_node(program.clone(), name)
+    }
+}
+
+/// Error that stops execution of a program
+#[derive(Debug, Clone, Error)]
+pub enum ExecutionError {
+    #[error("Entry point {0:?} not found in the main program")]
+    MissingEntryPoint(String),
+    #[error("Execution was aborted")]
+    Aborted,
+    #[error("Failed to write output: {0}")]
+    Output(String),
+    #[error("Node {node} expects {expected} inputs, got {got}")]
+    MissingInput {
+        node: AbsoluteNodeId,
+        expected: usize,
+        got: usize,
+    },
+    #[error("Input {socket_index} of node {node} expects {expected}, found {found}")]
+    SocketTypeMismatch {
+        node: AbsoluteNodeId,
+        socket_index: usize,
+        expected: String,
+        found: String,
+    },
+}
+
+/// Variables of an executor in text form, keyed by variable name and paired with the path of
+/// their class. Made with `Executor::snapshot`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub variables: BTreeMap<String, (ModulePath, String)>,
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum SnapshotError {
+    #[error("Class {0} is not loaded")]
+    UnknownClass(ModulePath),
+    #[error("Class {0} can't be constructed from a string")]
+    NotConstructible(ModulePath),
+    #[error("Failed to restore variable {variable:?}: {error}")]
+    Parse { variable: String, error: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        class::{Class, ProtoClass},
+        module::ModulePath,
+        node::AbsoluteNodeId,
+        object::Object,
+        program::PatchError,
+        stdlib::{number_class, StdPlugin},
+        test_utils::{main_path, SharedOutput, TestProgram},
+        ExecutionError, Executor, ExecutorBuilder, OutputBuffering, Plugin, Snapshot, VarEvent,
+    };
+    use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+    /// Main program that calls a subroutine of one node and ends
+    fn calling_program() -> TestProgram {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "subroutine", "subroutine:__main__@20:__main__@22")
+            .end(9, 0)
+            .chain(&[0, 1, 9])
+            .node(20, "start", "start#sub#[]")
+            .end(22, 0)
+            .chain(&[20, 22]);
+        program
+    }
+
+    #[test]
+    fn stack_trace_inside_subroutine() {
+        let mut executor = calling_program().executor();
+        executor.start_execution(false).unwrap();
+        executor.execute_step().unwrap();
+        assert_eq!(executor.stack_trace().len(), 1);
+        executor.execute_step().unwrap();
+        assert_eq!(
+            executor.current_node_id(),
+            Some(AbsoluteNodeId(main_path(), 22))
+        );
+        assert_eq!(
+            executor.stack_trace(),
+            [
+                AbsoluteNodeId(main_path(), 22),
+                AbsoluteNodeId(main_path(), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn step_over_and_into_subroutine() {
+        let at_call = || {
+            let mut executor = calling_program().executor();
+            executor.start_execution(false).unwrap();
+            executor.execute_step().unwrap();
+            assert_eq!(
+                executor.current_node_id(),
+                Some(AbsoluteNodeId(main_path(), 1))
+            );
+            executor
+        };
+        let mut stepped_over = at_call();
+        stepped_over.step_over().unwrap();
+        assert_eq!(
+            stepped_over.current_node_id(),
+            Some(AbsoluteNodeId(main_path(), 9))
+        );
+        let mut stepped_into = at_call();
+        stepped_into.execute_step().unwrap();
+        assert_eq!(
+            stepped_into.current_node_id(),
+            Some(AbsoluteNodeId(main_path(), 22))
+        );
+    }
+
+    #[test]
+    fn missing_input_is_an_error() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "number", "1")
+            .node(2, "equals", "eq")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0);
+        let mut executor = program.executor();
+        assert!(matches!(
+            executor.start_execution(true),
+            Err(ExecutionError::MissingInput { node, expected: 2, got: 1 })
+                if node == AbsoluteNodeId(main_path(), 2)
+        ));
+    }
+
+    #[test]
+    fn nodes_without_inputs_are_an_error() {
+        let cases = [
+            ("if", "if", 1),
+            ("bool", "from-object", 1),
+            ("variable_set", "set", 2),
+            ("subroutine", "subroutine:__main__@20:__main__@21", 1),
+        ];
+        for (class, variant, expected) in cases {
+            let mut program = TestProgram::new();
+            program
+                .start(0)
+                .node(1, class, variant)
+                .end(9, 0)
+                .chain(&[0, 1, 9])
+                .edge(1, 1, 9)
+                .node(20, "start", r#"start#sub#["any"]"#)
+                .end(21, 0)
+                .chain(&[20, 21]);
+            let mut executor = program.executor();
+            assert!(
+                matches!(
+                    executor.start_execution(true),
+                    Err(ExecutionError::MissingInput { node, expected: e, got: 0 })
+                        if node == AbsoluteNodeId(main_path(), 1) && e == expected
+                ),
+                "{class}"
+            );
+        }
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let mut program = TestProgram::new();
+        program.0.classes.push(ProtoClass {
+            name: "Point".into(),
+            nodes: vec![],
+        });
+        let values = [
+            ("point", "Point", r#"Point {"x": 1, "y": [2]}"#),
+            ("names", "std.array", r#"["a", "b, c", "\"d\""]"#),
+            (
+                "dict",
+                "std.dict",
+                r#"{"k": ["v", 1], 2: {"nested": "yes"}}"#,
+            ),
+            ("number", "std.number", "-1.5"),
+            ("string", "std.string", "plain text"),
+        ];
+        let mut executor = program.executor();
+        for (name, path, text) in values {
+            let class = executor.loaded.get_class(path.parse().unwrap()).unwrap();
+            let value = (class.obj_from_str.unwrap())(text).unwrap();
+            executor.set_variable(name, value);
+        }
+        let snapshot = executor.snapshot();
+        assert_eq!(
+            snapshot.variables["point"].0,
+            ModulePath(vec![], "Point".into())
+        );
+        assert_eq!(snapshot.variables["names"].0.to_string(), "std.array");
+        let snapshot: Snapshot = ron::from_str(&ron::to_string(&snapshot).unwrap()).unwrap();
+
+        let mut restored = program.executor();
+        restored.restore(&snapshot).unwrap();
+        for (name, _, _) in values {
+            let (original, value) = (
+                executor.get_variable(name).unwrap(),
+                restored.get_variable(name).unwrap(),
+            );
+            assert_eq!(value.class(), original.class());
+            assert_eq!(value.to_string(), original.to_string());
+        }
+        let names = restored.get_variable("names").unwrap();
+        assert_eq!(names.get_field(Rc::new(1.0)).as_string(), "b, c");
+    }
+
+    struct ExtPlugin;
+
+    impl Plugin for ExtPlugin {
+        fn classes(&self) -> HashMap<ModulePath, Class> {
+            [(
+                ModulePath(vec!["ext".into()], "number".into()),
+                number_class(),
+            )]
+            .into()
+        }
+    }
+
+    #[test]
+    fn snapshot_of_class_loaded_outside_std() {
+        let mut executor = Executor::default();
+        executor.load_plugin(ExtPlugin).unwrap();
+        executor.set_variable("n", Rc::new(2.5));
+        let snapshot = executor.snapshot();
+        assert_eq!(snapshot.variables["n"].0.to_string(), "ext.number");
+
+        let mut restored = Executor::default();
+        restored.load_plugin(ExtPlugin).unwrap();
+        restored.restore(&snapshot).unwrap();
+        assert_eq!(restored.get_variable("n").unwrap().as_number(), 2.5);
+    }
+
+    #[test]
+    fn uncastable_input_is_a_type_mismatch() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "string", "abc")
+            .node(2, "range", "range")
+            .const_input(2, 1, "4")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0);
+        let mut executor = program.executor();
+        let error = executor.start_execution(true).unwrap_err();
+        assert!(matches!(
+            &error,
+            ExecutionError::SocketTypeMismatch { node, socket_index: 0, expected, found }
+                if *node == AbsoluteNodeId(main_path(), 2) && expected == "number" && found == "string"
+        ));
+        assert_eq!(
+            error.to_string(),
+            "Input 0 of node __main__@2 expects number, found string"
+        );
+    }
+
+    /// Number fed into the string socket of a string length node
+    fn number_into_string_socket(strict: bool) -> Result<Vec<String>, ExecutionError> {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "number", "12345")
+            .node(2, "string_length", "chars")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0);
+        let mut executor = program.executor();
+        executor.set_strict_types(strict);
+        executor.start_execution(true)?;
+        let output = executor.final_output().unwrap();
+        Ok(output.iter().map(|value| value.to_string()).collect())
+    }
+
+    #[test]
+    fn lax_types_cast_inputs() {
+        assert_eq!(number_into_string_socket(false).unwrap(), ["5"]);
+    }
+
+    #[test]
+    fn strict_types_reject_casts() {
+        assert!(matches!(
+            number_into_string_socket(true),
+            Err(ExecutionError::SocketTypeMismatch { node, socket_index: 0, expected, found })
+                if node == AbsoluteNodeId(main_path(), 2) && expected == "string" && found == "number"
+        ));
+    }
+
+    #[test]
+    fn profile_counts_calls_per_class() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "string", "x")
+            .node(2, "print", "print")
+            .node(3, "print", "print")
+            .node(4, "print", "print")
+            .end(9, 0)
+            .chain(&[0, 1, 2, 3, 4, 9])
+            .connect(1, 0, 2, 0)
+            .connect(1, 0, 3, 0)
+            .connect(1, 0, 4, 0);
+        let mut executor = program.executor();
+        executor.set_output(Box::new(std::io::sink()));
+        executor.start_execution(true).unwrap();
+        assert!(executor.profile_report().is_empty());
+
+        executor.set_profiling(true);
+        executor.start_execution(true).unwrap();
+        let report = executor.profile_report();
+        let calls = |class: &str| {
+            let entry = report.iter().find(|(name, _, _)| name == class);
+            entry.map(|(_, _, calls)| *calls)
+        };
+        assert_eq!(calls("print"), Some(3));
+        assert_eq!(calls("cast"), Some(1));
+        assert_eq!(calls("if"), None);
+    }
+
+    #[test]
+    fn builder_configures_executor() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "print", "println")
+            .const_input(1, 0, "built")
+            .end(9, 0)
+            .chain(&[0, 1, 9]);
+        let output = SharedOutput::default();
+        let mut executor = ExecutorBuilder::new()
+            .plugin(StdPlugin)
+            .program(program.0, main_path())
+            .variable("answer", Rc::new(42.0))
+            .output(Box::new(output.clone()))
+            .profiling(true)
+            .build()
+            .unwrap();
+        executor.start_execution(true).unwrap();
+        assert_eq!(output.text(), "built\n");
+        assert_eq!(executor.get_variable("answer").unwrap().as_number(), 42.0);
+        assert!(!executor.profile_report().is_empty());
+    }
+
+    /// Executor with block-buffered output that is stopped right after printing a line
+    fn printed_but_not_ended(block_size: usize) -> (Executor, SharedOutput) {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "print", "println")
+            .const_input(1, 0, "hello")
+            .end(9, 0)
+            .chain(&[0, 1, 9]);
+        let output = SharedOutput::default();
+        let mut executor = program.executor();
+        executor.set_output(Box::new(output.clone()));
+        executor.set_output_buffering(OutputBuffering::BlockBuffered(block_size));
+        executor.start_execution(false).unwrap();
+        executor.execute_step().unwrap();
+        executor.execute_step().unwrap();
+        (executor, output)
+    }
+
+    #[test]
+    fn block_buffered_output_is_written_on_flush() {
+        let (mut executor, output) = printed_but_not_ended(64);
+        assert_eq!(output.text(), "");
+        executor.flush_output().unwrap();
+        assert_eq!(output.text(), "hello\n");
+    }
+
+    #[test]
+    fn block_buffered_output_is_written_on_end() {
+        let (mut executor, output) = printed_but_not_ended(64);
+        assert_eq!(output.text(), "");
+        executor.resume_auto().unwrap();
+        assert_eq!(output.text(), "hello\n");
+    }
+
+    #[test]
+    fn full_block_is_written() {
+        let (_, output) = printed_but_not_ended(4);
+        assert_eq!(output.text(), "hello\n");
+    }
+
+    #[test]
+    fn variable_access_is_traced() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "variable_get", "get")
+            .const_input(1, 0, "x")
+            .node(2, "variable_set", "set")
+            .const_input(2, 0, "x")
+            .const_input(2, 1, "5")
+            .node(3, "variable_get", "get")
+            .const_input(3, 0, "x")
+            .end(9, 0)
+            .chain(&[0, 1, 2, 3, 9]);
+        let events = Rc::new(RefCell::new(vec![]));
+        let mut executor = program.executor();
+        let recorded = Rc::clone(&events);
+        executor.set_variable_tracer(Some(Box::new(move |event| {
+            recorded.borrow_mut().push(match event {
+                VarEvent::Read { name, value: None } => format!("read {name}: miss"),
+                VarEvent::Read {
+                    name,
+                    value: Some(value),
+                } => format!("read {name}: {value}"),
+                VarEvent::Write { name, value } => format!("write {name}: {value}"),
+            })
+        })));
+        executor.start_execution(true).unwrap();
+        assert_eq!(
+            *events.borrow(),
+            ["read x: miss", "write x: 5", "read x: 5"]
+        );
+
+        executor.set_variable_tracer(None);
+        executor.get_variable("x");
+        assert_eq!(events.borrow().len(), 3);
+    }
+
+    #[test]
+    fn reset_runs_again_from_scratch() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "accumulate", "add")
+            .const_input(1, 0, "runs")
+            .end(9, 1)
+            .chain(&[0, 1, 9])
+            .connect(1, 0, 9, 0);
+        let mut executor = program.executor();
+        let run = |executor: &mut Executor| {
+            executor.start_execution(true).unwrap();
+            executor.final_output().unwrap()[0].to_string()
+        };
+        assert_eq!(run(&mut executor), "1");
+        executor.reset();
+        assert!(executor.final_output().is_none());
+        assert!(executor.get_variable("runs").is_none());
+        assert_eq!(run(&mut executor), "1");
+
+        executor.reset();
+        executor.start_execution(false).unwrap();
+        executor.execute_step().unwrap();
+        executor.execute_step().unwrap();
+        assert!(executor.current_node_id().is_some());
+        executor.reset();
+        assert!(executor.current_node_id().is_none());
+        assert_eq!(run(&mut executor), "1");
+    }
+
+    /// Run a program on this thread while another thread aborts it
+    fn run_aborted(program: &TestProgram) -> Result<(), ExecutionError> {
+        let mut executor = program.executor();
+        let handle = executor.abort_handle();
+        let aborter = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            handle.abort();
+        });
+        let result = executor.start_execution(true);
+        aborter.join().unwrap();
+        result
+    }
+
+    #[test]
+    fn infinite_loop_is_aborted() {
+        let mut program = TestProgram::new();
+        program.start(0).node(1, "nop", "nop").chain(&[0, 1, 1]);
+        assert!(matches!(
+            run_aborted(&program),
+            Err(ExecutionError::Aborted)
+        ));
+    }
+
+    #[test]
+    fn infinite_loop_in_nested_subroutine_is_aborted() {
+        // The subroutine is run to completion by the retry node, inside a single step
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "retry", "retry")
+            .const_input(1, 0, "subroutine:__main__@20:__main__@22")
+            .const_input(1, 1, "1")
+            .end(9, 0)
+            .chain(&[0, 1, 9])
+            .node(20, "start", "start#sub#[]")
+            .node(21, "nop", "nop")
+            .end(22, 0)
+            .chain(&[20, 21, 21]);
+        assert!(matches!(
+            run_aborted(&program),
+            Err(ExecutionError::Aborted)
+        ));
+    }
+
+    #[test]
+    fn const_input_changes_between_runs() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "number", "1")
+            .end(9, 1)
+            .chain(&[0, 1, 9])
+            .connect(1, 0, 9, 0);
+        let mut executor = program.executor();
+        let node = AbsoluteNodeId(main_path(), 1);
+        executor.start_execution(true).unwrap();
+        assert_eq!(executor.final_output().unwrap()[0].to_string(), "1");
+        assert_eq!(executor.node_const_input(&node, 0), Some("1"));
+
+        executor.set_node_const_input(&node, 0, "2".into()).unwrap();
+        assert_eq!(executor.node_const_input(&node, 0), Some("2"));
+        executor.start_execution(true).unwrap();
+        assert_eq!(executor.final_output().unwrap()[0].to_string(), "2");
+    }
+
+    #[test]
+    fn invalid_const_input_is_rejected() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "number_theory", "gcd")
+            .end(9, 0)
+            .chain(&[0, 1, 9]);
+        let mut executor = program.executor();
+        let node = AbsoluteNodeId(main_path(), 1);
+        assert!(matches!(
+            executor.set_node_const_input(&node, 0, "twelve".into()),
+            Err(PatchError::InvalidConstInput { node: 1, input: 0, value, .. }) if value == "twelve"
+        ));
+        assert_eq!(executor.node_const_input(&node, 0), None);
+        executor
+            .set_node_const_input(&node, 0, "12".into())
+            .unwrap();
+    }
+
+    #[test]
+    fn const_input_of_socket_from_variant() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .end(9, 1)
+            .const_input(9, 0, "5")
+            .chain(&[0, 9]);
+        assert_eq!(program.run_strings(), ["5"]);
+        let mut executor = program.executor();
+        executor
+            .set_node_const_input(&AbsoluteNodeId(main_path(), 9), 0, "6".into())
+            .unwrap();
+        executor.start_execution(true).unwrap();
+        assert_eq!(executor.final_output().unwrap()[0].to_string(), "6");
+    }
+
+    #[test]
+    fn abort_inside_predicate_resumes_the_calling_node() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "array", "[1, 3, 4, 5]")
+            .node(2, "find_index", "find_index")
+            .const_input(2, 1, "subroutine:__main__@20:__main__@29")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0)
+            .node(20, "start", r#"start#sub#["any"]"#)
+            .node(21, "native", "native:is_even:1:1")
+            .end(29, 1)
+            .chain(&[20, 21, 29])
+            .connect(20, 0, 21, 0)
+            .connect(21, 0, 29, 0);
+        let mut executor = program.executor();
+        let handle = executor.abort_handle();
+        let calls = Rc::new(RefCell::new(0));
+        let counted = Rc::clone(&calls);
+        executor.register_native(
+            "is_even",
+            Box::new(move |args| {
+                // Abort while the predicate checks the first element
+                *counted.borrow_mut() += 1;
+                if *counted.borrow() == 1 {
+                    handle.abort();
+                }
+                vec![Rc::new(args[0].as_number() % 2.0 == 0.0) as Rc<dyn Object>]
+            }),
+        );
+        assert!(matches!(
+            executor.start_execution(true),
+            Err(ExecutionError::Aborted)
+        ));
+        assert_eq!(
+            executor.current_node_id(),
+            Some(AbsoluteNodeId(main_path(), 2))
+        );
+        executor.resume_auto().unwrap();
+        assert_eq!(executor.final_output().unwrap()[0].to_string(), "2");
+        assert_eq!(*calls.borrow(), 4);
+    }
 }
@@ -1,6 +1,6 @@
 use crate::{class::Class, object::Object};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, fmt::Display, rc::Rc, str::FromStr};
+use std::{collections::BTreeMap, fmt::Display, rc::Rc, str::FromStr};
 use thiserror::Error;
 
 /// Path in the module
@@ -54,9 +54,10 @@ impl<'de> Deserialize<'de> for ModulePath {
 }
 
 /// Used to index items across programs/packages. Built with executor upon loading programs.
+/// Items are kept sorted by name, so traversal order is always the same.
 #[derive(Debug, Clone, Default)]
 pub struct Module {
-    pub items: HashMap<String, ModuleItem>,
+    pub items: BTreeMap<String, ModuleItem>,
 }
 
 impl Module {
@@ -88,6 +89,33 @@ impl Module {
         let ModuleItem::Class(class) = current_segment.get_mut(&path.1)? else {return None};
         Some(class)
     }
+
+    /// All classes in this module and its submodules, sorted by path
+    pub fn classes(&self) -> Vec<(ModulePath, &Class)> {
+        let mut classes = vec![];
+        self.collect_classes(&mut vec![], &mut classes);
+        classes
+    }
+
+    fn collect_classes<'a>(
+        &'a self,
+        prefix: &mut Vec<String>,
+        classes: &mut Vec<(ModulePath, &'a Class)>,
+    ) {
+        for (name, item) in &self.items {
+            match item {
+                ModuleItem::Class(class) => {
+                    classes.push((ModulePath(prefix.clone(), name.clone()), class))
+                }
+                ModuleItem::Module(module) => {
+                    prefix.push(name.clone());
+                    module.collect_classes(prefix, classes);
+                    prefix.pop();
+                }
+                ModuleItem::Constant(_) => {}
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -103,3 +131,37 @@ impl From<Class> for ModuleItem {
         Self::Class(c)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(name: &str) -> Class {
+        Class {
+            name: name.into(),
+            nodes: vec![],
+            obj_from_str: None,
+        }
+    }
+
+    #[test]
+    fn classes_are_sorted_by_path() {
+        let paths = ["std.string", "b.z", "a", "std.array", "b.c.d", "std.any"];
+        let mut module = Module::default();
+        for path in paths {
+            let path: ModulePath = path.parse().unwrap();
+            module.insert(path.clone(), class(&path.1));
+        }
+        let listed: Vec<String> = module
+            .classes()
+            .into_iter()
+            .map(|(path, _)| path.to_string())
+            .collect();
+        assert_eq!(
+            listed,
+            ["a", "b.c.d", "b.z", "std.any", "std.array", "std.string"]
+        );
+        let path = "std.array".parse().unwrap();
+        assert_eq!(module.get_class(&path).unwrap().name, "array");
+    }
+}
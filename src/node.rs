@@ -3,7 +3,7 @@ use crate::{
     module::{ModulePath, ModulePathParseError},
     program::ProgramId,
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -33,13 +33,10 @@ impl FromStr for AbsoluteNodeId {
     type Err = AbsoluteNodeIdParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut seq: Vec<String> = s.split('@').map(String::from).collect();
-        let node_id: NodeId = seq
-            .pop()
-            .ok_or(AbsoluteNodeIdParseError::IdNotFound)?
-            .parse()?;
-        let path: ProgramId = seq[0].parse()?;
-        Ok(Self(path, node_id))
+        let (path, node_id) = s
+            .rsplit_once('@')
+            .ok_or(AbsoluteNodeIdParseError::IdNotFound)?;
+        Ok(Self(path.parse()?, node_id.parse()?))
     }
 }
 
@@ -65,6 +62,23 @@ impl From<ModulePathParseError> for AbsoluteNodeIdParseError {
     }
 }
 
+/// A node variant that could not be set
+#[derive(Debug, Clone, Error)]
+#[error("Invalid variant {variant:?}: {reason}")]
+pub struct VariantError {
+    pub variant: String,
+    pub reason: String,
+}
+
+impl VariantError {
+    pub fn new(variant: &str, reason: impl Display) -> Self {
+        Self {
+            variant: variant.into(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
 /// ID of a branch of node
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct NodeBranchId(pub NodeId, pub usize);
@@ -77,8 +91,7 @@ impl From<&NodeBranchId> for u64 {
 
 impl From<u64> for NodeBranchId {
     fn from(n: u64) -> Self {
-        let branch_idx: u32 = (((1 << 33) - 1) & n) as u32;
-        let node_id: NodeId = ((((1 << 33) - 1) << 32) & n) as NodeId;
+        let (node_id, branch_idx) = ((n >> 32) as NodeId, n as u32);
         Self(node_id, branch_idx as usize)
     }
 }
@@ -102,8 +115,8 @@ impl<'de> Deserialize<'de> for NodeBranchId {
 }
 
 pub trait Node: Debug {
-    /// Execution of the node's code. Returns a branch index.
-    fn execute(&self, context: &mut ExecutionContext) -> usize;
+    /// Execution of the node's code. Returns a branch index, or an error that stops execution.
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError>;
 
     /// The class of the node
     fn class(&self) -> Class;
@@ -117,6 +130,22 @@ pub trait Node: Debug {
     /// Set a specific variant of a node
     fn set_variant(&mut self, variant: &str);
 
+    /// Set a specific variant of a node, reporting malformed variants instead of panicking. By
+    /// default calls `set_variant`.
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.set_variant(variant);
+        Ok(())
+    }
+
+    /// Variants of a node paired with human-readable descriptions, for editors. By default
+    /// descriptions are empty.
+    fn variant_descriptions(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        self.variants()
+            .into_iter()
+            .map(|variant| (variant, Cow::Borrowed("")))
+            .collect()
+    }
+
     /// Whether variation can be set as a custom string (not listed in `variants`) or not
     fn accepts_arbitrary_variants(&self) -> bool {
         false
@@ -174,6 +203,34 @@ impl NodeStorage {
     }
 }
 
+/// Description of a placed node's interface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeSignature {
+    pub class: Class,
+    pub variant: String,
+    pub variant_descriptions: Vec<(String, String)>,
+    pub inputs: Vec<InputSocket>,
+    pub outputs: Vec<OutputSocket>,
+    pub branches: u32,
+}
+
+impl NodeSignature {
+    pub fn of(node: &dyn Node) -> Self {
+        Self {
+            class: node.class(),
+            variant: node.current_variant().into_owned(),
+            variant_descriptions: node
+                .variant_descriptions()
+                .into_iter()
+                .map(|(variant, description)| (variant.into_owned(), description.into_owned()))
+                .collect(),
+            inputs: node.inputs(),
+            outputs: node.outputs(),
+            branches: node.branches(),
+        }
+    }
+}
+
 /// Information about a node stored in the program
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeInfo {
@@ -181,3 +238,12 @@ pub struct NodeInfo {
     pub idx: usize,
     pub variant: String,
 }
+
+/// Editor-only information about a node, not used at runtime
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeMetadata {
+    pub label: Option<String>,
+    pub comment: Option<String>,
+    /// Color tag, format is up to the editor
+    pub color: Option<String>,
+}
@@ -1,5 +1,6 @@
-use crate::class::Class;
+use crate::{class::Class, module::ModulePath};
 use std::{
+    any::Any,
     cmp::Ordering,
     error::Error,
     fmt::{Debug, Display},
@@ -26,6 +27,30 @@ where
     }
 }
 
+/// 64-bit FNV-1a of the name of a class followed by data of an object, used by
+/// [`Object::object_hash`]
+pub fn class_hash(class_name: &str, data: impl IntoIterator<Item = u8>) -> u64 {
+    class_name
+        .bytes()
+        .chain([0])
+        .chain(data)
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+/// Access to the concrete type of an object, used by `downcast_ref` of `dyn Object`. Implemented
+/// for all objects.
+pub trait ObjectAny {
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Object + 'static> ObjectAny for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
 /// Stainless Script Object version of [`PartialEq`]
 pub trait ObjectPartialEq {
     fn eq(&self, other: Rc<dyn Object>) -> bool;
@@ -68,7 +93,14 @@ pub trait ObjectOrd: ObjectEq + ObjectPartialOrd {
 /// The object of a data type. Data type is derived from the object's class. Methods specified here
 /// are for use in nodes mostly.
 pub trait Object:
-    Display + Debug + ObjectFromStr + ObjectPartialEq + ObjectPartialOrd + ObjectEq + ObjectOrd
+    Display
+    + Debug
+    + ObjectAny
+    + ObjectFromStr
+    + ObjectPartialEq
+    + ObjectPartialOrd
+    + ObjectEq
+    + ObjectOrd
 {
     fn class(&self) -> Class;
     /// Since Object requires Display, this has little use and is implemented  through ToString,
@@ -93,6 +125,35 @@ pub trait Object:
         unimplemented!()
     }
 
+    /// Names of fields that can be accessed with string keys through `get_field`. Default
+    /// implementation returns no names because most types don't have fields.
+    fn field_names(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// Clone the object itself instead of it wrapped in Rc. Needed to modify fields of an
+    /// object that is shared. Default implementation is `unimplemented!()` because most types
+    /// don't have fields.
+    fn clone_object(&self) -> Rc<dyn Object> {
+        unimplemented!()
+    }
+
+    /// Text form of the object together with the path of the class that restores it through
+    /// `obj_from_str`. A path without a module prefix is resolved by the executor to wherever a
+    /// class of that name is loaded. Default implementation pairs the [`Display`] text with the
+    /// name of the class. Objects whose text can't be parsed back override it.
+    fn serialize_with_class(&self) -> (ModulePath, String) {
+        (ModulePath(vec![], self.class().name), self.to_string())
+    }
+
+    /// Hash that stays the same between runs and builds, for caching and deduplication. Objects
+    /// that are equal hash equally. Default implementation hashes the class name together with
+    /// the text form of the object, so objects that are equal to objects with a different text
+    /// form override it.
+    fn object_hash(&self) -> u64 {
+        class_hash(&self.class().name, self.to_string().bytes())
+    }
+
     fn cast_to(&self, to: &Class) -> Rc<dyn Object> {
         if self.class().name == "any" {
             (to.obj_from_str.unwrap())(&self.as_string()).unwrap()
@@ -100,4 +161,18 @@ pub trait Object:
             unimplemented!()
         }
     }
+
+    /// Fallible cast. Default implementation parses string representation of the object with
+    /// the target class's `obj_from_str`, `None` if the class can't be made from a string or
+    /// parsing fails.
+    fn try_cast_to(&self, to: &Class) -> Option<Rc<dyn Object>> {
+        (to.obj_from_str?)(&self.as_string()).ok()
+    }
+}
+
+impl dyn Object {
+    /// The object as a specific type, if it is of that type
+    pub fn downcast_ref<T: Object + 'static>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
 }
@@ -1,16 +1,22 @@
 use crate::{
     class::{Class, ProtoClass},
-    module::{Module, ModulePath},
-    node::{AbsoluteNodeId, Node, NodeBranchId, NodeId, NodeInfo, NodeStorage},
-    object::Object,
-    socket::{Connection, InputSocketId},
+    module::{Module, ModulePath, ModulePathParseError},
+    node::{
+        AbsoluteNodeId, Node, NodeBranchId, NodeId, NodeInfo, NodeMetadata, NodeStorage,
+        VariantError,
+    },
+    object::{Object, ObjectFromStr},
+    socket::{Connection, InputSocketId, SocketId},
+    stdlib::{AnyType, Instance, Subroutine},
     Plugin,
 };
 use serde::{Deserialize, Serialize};
 use std::{
     collections::{BTreeMap, HashMap, HashSet},
     rc::Rc,
+    str::FromStr,
 };
+use thiserror::Error;
 
 /// ID of a program, constructed by an executor
 pub type ProgramId = ModulePath;
@@ -19,6 +25,7 @@ pub type ProgramId = ModulePath;
 pub struct LoadedProgram {
     pub nodes: NodeStorage,
     pub branch_edges: HashMap<NodeBranchId, NodeId>,
+    pub fallback_edges: HashMap<NodeId, NodeId>,
     pub connections: HashMap<Connection, Option<Rc<dyn Object>>>,
     pub const_inputs: HashMap<InputSocketId, String>,
 }
@@ -28,6 +35,7 @@ impl From<&Program> for LoadedProgram {
         Self {
             nodes: NodeStorage::default(),
             branch_edges: p.branch_edges.clone(),
+            fallback_edges: p.fallback_edges.clone().unwrap_or_default(),
             connections: p
                 .connections
                 .iter()
@@ -72,19 +80,113 @@ impl LoadedProgram {
         node_id: NodeId,
         node: &NodeInfo,
         class: &Class,
-    ) -> Rc<dyn Node> {
-        assert_eq!(node.class.1, class.name);
-        let mut loaded_node = class.nodes[node.idx].clone_node();
+    ) -> Result<Rc<dyn Node>, PatchError> {
+        if node.class.1 != class.name {
+            return Err(PatchError::ClassMismatch {
+                class: node.class.clone(),
+                name: class.name.clone(),
+            });
+        }
+        let mut loaded_node = class
+            .nodes
+            .get(node.idx)
+            .ok_or_else(|| PatchError::InvalidNodeIndex {
+                class: node.class.clone(),
+                idx: node.idx,
+            })?
+            .clone_node();
         Rc::get_mut(&mut loaded_node)
             .unwrap()
-            .set_variant(&node.variant);
+            .try_set_variant(&node.variant)?;
+        // Defaults are parsed every time the input is read, so they are checked once here
+        for (idx, socket) in loaded_node.inputs().iter().enumerate() {
+            if let Some(default) = &socket.default {
+                let from_str = socket
+                    .class
+                    .obj_from_str
+                    .unwrap_or(<AnyType as ObjectFromStr>::from_str);
+                from_str(default).map_err(|e| {
+                    VariantError::new(
+                        &node.variant,
+                        format!("invalid default of input {idx}: {e}"),
+                    )
+                })?;
+            }
+        }
         self.nodes.insert_node_at(node_id, Rc::clone(&loaded_node));
-        loaded_node as Rc<dyn Node>
+        Ok(loaded_node as Rc<dyn Node>)
+    }
+
+    /// Insert a node or replace an existing one with the same id
+    pub fn upsert_node(
+        &mut self,
+        node_id: NodeId,
+        node: NodeInfo,
+        class: &Class,
+    ) -> Result<Rc<dyn Node>, PatchError> {
+        self.insert_raw_node_at(node_id, &node, class)
+    }
+
+    pub fn set_branch_edge(&mut self, branch: NodeBranchId, next: NodeId) {
+        self.branch_edges.insert(branch, next);
+    }
+
+    pub fn remove_branch_edge(&mut self, branch: &NodeBranchId) -> Option<NodeId> {
+        self.branch_edges.remove(branch)
+    }
+
+    pub fn set_fallback_edge(&mut self, node: NodeId, next: NodeId) {
+        self.fallback_edges.insert(node, next);
     }
 
+    pub fn remove_fallback_edge(&mut self, node: NodeId) -> Option<NodeId> {
+        self.fallback_edges.remove(&node)
+    }
+
+    /// New connection carries no value until its output node is executed
+    pub fn add_connection(&mut self, connection: Connection) {
+        self.connections.entry(connection).or_insert(None);
+    }
+
+    pub fn remove_connection(&mut self, connection: &Connection) {
+        self.connections.remove(connection);
+    }
+
+    pub fn get_const_input(&self, socket: InputSocketId) -> Option<&str> {
+        self.const_inputs.get(&socket).map(String::as_str)
+    }
+
+    /// Values for inputs of placed nodes have to be readable as the class of the input
+    pub fn set_const_input(
+        &mut self,
+        socket: InputSocketId,
+        value: String,
+    ) -> Result<(), PatchError> {
+        let SocketId(node, input) = socket.0;
+        if let Some(input_socket) = self
+            .get_node(node)
+            .and_then(|node| node.inputs().into_iter().nth(input))
+        {
+            let from_str = input_socket
+                .class
+                .obj_from_str
+                .unwrap_or(<AnyType as ObjectFromStr>::from_str);
+            from_str(&value).map_err(|e| PatchError::InvalidConstInput {
+                node,
+                input,
+                value: value.clone(),
+                message: e.to_string(),
+            })?;
+        }
+        self.const_inputs.insert(socket, value);
+        Ok(())
+    }
+
+    /// Branches without an edge continue at the fallback of the node, if it has one
     pub fn get_next_node(&self, current: NodeId, branch: usize) -> Option<NodeId> {
         self.branch_edges
             .get(&NodeBranchId(current, branch))
+            .or_else(|| self.fallback_edges.get(&current))
             .copied()
     }
 
@@ -105,11 +207,19 @@ impl LoadedProgram {
         }
     }
 
+    /// Forget values passed through connections
+    pub fn clear_outputs(&mut self) {
+        for value in self.connections.values_mut() {
+            *value = None;
+        }
+    }
+
     /// Get inputs of a node from connections that end in the specified node, as well as collect
     /// const inputs (generally, assumed they are present where it's not  provideds by a
-    /// connection. Although the connection mightt be empty, so this is kinda handled.)
+    /// connection. Although the connection mightt be empty, so this is kinda handled.) Inputs
+    /// that are still missing are filled with socket defaults, if there are any.
     pub fn get_inputs(&self, node_id: NodeId) -> Vec<Option<Rc<dyn Object>>> {
-        let connections: BTreeMap<usize, Rc<dyn Object>> = self
+        let mut connections: BTreeMap<usize, Rc<dyn Object>> = self
             .connections
             .iter()
             .filter_map(|(c, i)| {
@@ -117,23 +227,31 @@ impl LoadedProgram {
             })
             .chain(self.const_inputs.iter().filter_map(|(s, v)| {
                 let inputs = self.get_node(node_id).unwrap().inputs();
-                let class = &inputs.get(s.0 .1)?.class;
-                (s.0 .0 == node_id).then(|| {
-                    (
-                        s.0 .1,
-                        class.obj_from_str.expect(
-                            "Class does not have object from str conversion in const input",
-                        )(v)
-                        .unwrap(),
-                    )
-                })
+                // Like defaults, const inputs of sockets deserialized from node variants are
+                // read as `any`
+                let from_str = inputs
+                    .get(s.0 .1)?
+                    .class
+                    .obj_from_str
+                    .unwrap_or(<AnyType as ObjectFromStr>::from_str);
+                (s.0 .0 == node_id).then(|| (s.0 .1, from_str(v).unwrap()))
             }))
             .collect();
-        let mut result: Vec<Option<Rc<dyn Object>>> = Vec::with_capacity(connections.keys().len());
-        for i in 0..=connections.keys().copied().max().unwrap_or(0) {
-            result.push(connections.get(&i).cloned())
+        for (i, socket) in self.get_node(node_id).unwrap().inputs().iter().enumerate() {
+            if let Some(default) = &socket.default {
+                // Sockets deserialized from node variants only know the class name, their
+                // defaults are treated as `any`
+                let from_str = socket
+                    .class
+                    .obj_from_str
+                    .unwrap_or(<AnyType as ObjectFromStr>::from_str);
+                connections
+                    .entry(i)
+                    .or_insert_with(|| from_str(default).expect("checked when the node is placed"));
+            }
         }
-        result
+        let len = connections.keys().copied().max().map_or(0, |i| i + 1);
+        (0..len).map(|i| connections.get(&i).cloned()).collect()
     }
 }
 
@@ -144,51 +262,109 @@ pub struct LoadedProgramData {
 }
 
 impl LoadedProgramData {
-    pub fn load_plugin(&mut self, plugin: impl Plugin) {
-        for (path, class) in plugin.classes() {
+    pub fn load_plugin(&mut self, plugin: impl Plugin) -> Result<(), LoadError> {
+        let classes = plugin.classes();
+        if let Some(path) = classes
+            .keys()
+            .find(|path| self.modules.get_class(path).is_some())
+        {
+            return Err(LoadError::DuplicateClass(path.clone()));
+        }
+        for (path, class) in classes {
             self.modules.insert(path, class);
         }
+        Ok(())
     }
 
-    pub fn load_program(&mut self, path: &ProgramId, program: &Program) {
-        let imported_classes: Vec<(ModulePath, Vec<NodeId>)> = program
+    pub fn load_program(&mut self, path: &ProgramId, program: &Program) -> Result<(), LoadError> {
+        let class_paths: Vec<ModulePath> = program
             .classes
             .iter()
-            .map(|pc| {
-                let mut class_path = path.clone();
-                class_path.1 = pc.name.clone();
-                let class = Class {
-                    name: pc.name.clone(),
-                    nodes: vec![],
-                    obj_from_str: None, // TODO: Add a generic class initializer when
-                                        // DeserializeObject is implemented
-                };
-                self.modules.insert(class_path.clone(), class);
-                (class_path, pc.nodes.clone())
-            })
+            .map(|pc| ModulePath(path.0.clone(), pc.name.clone()))
             .collect();
-        let inserted_program = self
+        if let Some(class_path) = class_paths
+            .iter()
+            .find(|class_path| self.modules.get_class(class_path).is_some())
+        {
+            return Err(LoadError::DuplicateClass(class_path.clone()));
+        }
+        if let Some((node_id, node)) = program.nodes.iter().find(|(_, node)| {
+            self.modules.get_class(&node.class).is_none() && !class_paths.contains(&node.class)
+        }) {
+            return Err(LoadError::UnknownClass {
+                node: AbsoluteNodeId(path.clone(), *node_id),
+                class: node.class.clone(),
+            });
+        }
+        // Nodes are placed before anything is loaded, so a program that fails to load leaves
+        // nothing behind
+        let mut loaded_program = self
             .programs
-            .entry(path.clone())
-            .or_insert_with(|| program.into());
-        for (node_id, node) in &program.nodes {
+            .get(path)
+            .cloned()
+            .unwrap_or_else(|| program.into());
+        // Nodes of classes defined by the program copy the method nodes of the class, so they are
+        // placed after the rest
+        let (own_class_nodes, other_nodes): (Vec<_>, Vec<_>) = program
+            .nodes
+            .iter()
+            .partition(|(_, node)| class_paths.contains(&node.class));
+        for (node_id, node) in other_nodes {
             let class = self.modules.get_class(&node.class).unwrap();
-            inserted_program.insert_raw_node_at(*node_id, node, class);
+            loaded_program
+                .insert_raw_node_at(*node_id, node, class)
+                .map_err(|error| {
+                    LoadError::placing(AbsoluteNodeId(path.clone(), *node_id), error)
+                })?;
         }
-        for (class_path, node_ids) in imported_classes {
-            let class = self.modules.get_class_mut(&class_path).unwrap();
-            let loaded_nodes = node_ids
+        let classes = program
+            .classes
+            .iter()
+            .zip(&class_paths)
+            .map(|(pc, class_path)| {
+                let nodes = pc
+                    .nodes
+                    .iter()
+                    .map(|id| {
+                        loaded_program
+                            .get_node(*id)
+                            .ok_or_else(|| LoadError::MissingMethodNode {
+                                class: class_path.clone(),
+                                node: AbsoluteNodeId(path.clone(), *id),
+                            })
+                    })
+                    .collect::<Result<_, _>>()?;
+                let class = Class {
+                    name: pc.name.clone(),
+                    nodes,
+                    obj_from_str: Some(<Instance as ObjectFromStr>::from_str),
+                };
+                Ok((class_path.clone(), class))
+            })
+            .collect::<Result<Vec<_>, LoadError>>()?;
+        for (node_id, node) in own_class_nodes {
+            let (_, class) = classes
                 .iter()
-                .map(|id| inserted_program.get_node(*id).unwrap())
-                .collect();
-            class.nodes = loaded_nodes;
+                .find(|(class_path, _)| *class_path == node.class)
+                .unwrap();
+            loaded_program
+                .insert_raw_node_at(*node_id, node, class)
+                .map_err(|error| {
+                    LoadError::placing(AbsoluteNodeId(path.clone(), *node_id), error)
+                })?;
         }
+        for (class_path, class) in classes {
+            self.modules.insert(class_path, class);
+        }
+        self.programs.insert(path.clone(), loaded_program);
+        Ok(())
     }
 
-    pub fn load_programs(&mut self, programs: &ProgramCollection) {
+    pub fn load_programs(&mut self, programs: &ProgramCollection) -> Result<(), LoadError> {
         for (path, program) in &programs.programs {
-            self.load_program(path, program)
+            self.load_program(path, program)?
         }
+        Ok(())
     }
 
     pub fn get_node(&self, node_id: &AbsoluteNodeId) -> Option<Rc<dyn Node>> {
@@ -221,9 +397,102 @@ impl LoadedProgramData {
         self.programs.get(&node_id.0).unwrap().get_inputs(node_id.1)
     }
 
+    /// Forget values passed through connections in all programs
+    pub fn clear_outputs(&mut self) {
+        for program in self.programs.values_mut() {
+            program.clear_outputs();
+        }
+    }
+
     pub fn get_class(&self, path: ModulePath) -> Option<&Class> {
         self.modules.get_class(&path)
     }
+
+    fn program_mut(&mut self, program_id: &ProgramId) -> Result<&mut LoadedProgram, PatchError> {
+        self.programs
+            .get_mut(program_id)
+            .ok_or_else(|| PatchError::UnknownProgram(program_id.clone()))
+    }
+
+    pub fn upsert_node(
+        &mut self,
+        node_id: &AbsoluteNodeId,
+        node: NodeInfo,
+    ) -> Result<Rc<dyn Node>, PatchError> {
+        let class = self
+            .modules
+            .get_class(&node.class)
+            .ok_or_else(|| PatchError::UnknownClass(node.class.clone()))?;
+        let program = self
+            .programs
+            .get_mut(&node_id.0)
+            .ok_or_else(|| PatchError::UnknownProgram(node_id.0.clone()))?;
+        program.upsert_node(node_id.1, node, class)
+    }
+
+    pub fn set_branch_edge(
+        &mut self,
+        program_id: &ProgramId,
+        branch: NodeBranchId,
+        next: NodeId,
+    ) -> Result<(), PatchError> {
+        self.program_mut(program_id)?.set_branch_edge(branch, next);
+        Ok(())
+    }
+
+    pub fn remove_branch_edge(
+        &mut self,
+        program_id: &ProgramId,
+        branch: &NodeBranchId,
+    ) -> Option<NodeId> {
+        self.programs
+            .get_mut(program_id)?
+            .remove_branch_edge(branch)
+    }
+
+    pub fn set_fallback_edge(
+        &mut self,
+        program_id: &ProgramId,
+        node: NodeId,
+        next: NodeId,
+    ) -> Result<(), PatchError> {
+        self.program_mut(program_id)?.set_fallback_edge(node, next);
+        Ok(())
+    }
+
+    pub fn remove_fallback_edge(&mut self, program_id: &ProgramId, node: NodeId) -> Option<NodeId> {
+        self.programs
+            .get_mut(program_id)?
+            .remove_fallback_edge(node)
+    }
+
+    pub fn add_connection(
+        &mut self,
+        program_id: &ProgramId,
+        connection: Connection,
+    ) -> Result<(), PatchError> {
+        self.program_mut(program_id)?.add_connection(connection);
+        Ok(())
+    }
+
+    pub fn remove_connection(&mut self, program_id: &ProgramId, connection: &Connection) {
+        if let Some(program) = self.programs.get_mut(program_id) {
+            program.remove_connection(connection)
+        }
+    }
+
+    pub fn get_const_input(&self, program_id: &ProgramId, socket: InputSocketId) -> Option<&str> {
+        self.programs.get(program_id)?.get_const_input(socket)
+    }
+
+    pub fn set_const_input(
+        &mut self,
+        program_id: &ProgramId,
+        socket: InputSocketId,
+        value: String,
+    ) -> Result<(), PatchError> {
+        self.program_mut(program_id)?.set_const_input(socket, value)
+    }
 }
 
 /// Collection of programs loaded into an executor
@@ -232,6 +501,194 @@ pub struct ProgramCollection {
     pub programs: HashMap<ProgramId, Program>,
 }
 
+impl ProgramCollection {
+    /// Recursively read imports of a program and add them to the collection. Programs that are
+    /// already in the collection are not read again. The program itself is not added.
+    pub fn resolve_imports(
+        &mut self,
+        path: ProgramId,
+        program: &Program,
+        resolver: &mut impl ImportResolver,
+    ) -> Result<(), ImportError> {
+        self.resolve_imports_chain(&mut vec![path], program, resolver)
+    }
+
+    fn resolve_imports_chain(
+        &mut self,
+        chain: &mut Vec<ProgramId>,
+        program: &Program,
+        resolver: &mut impl ImportResolver,
+    ) -> Result<(), ImportError> {
+        for import in program.imports.iter().flatten() {
+            let path = ModulePath::from_str(import)?;
+            if chain.contains(&path) {
+                chain.push(path);
+                return Err(ImportError::Cyclic(chain.clone()));
+            }
+            if self.programs.contains_key(&path) {
+                continue;
+            }
+            let imported_program = resolver.read_import(&path)?;
+            chain.push(path.clone());
+            self.resolve_imports_chain(chain, &imported_program, resolver)?;
+            chain.pop();
+            self.programs.insert(path, imported_program);
+        }
+        Ok(())
+    }
+
+    /// Find likely editing mistakes in the programs, such as nodes that can never be executed.
+    /// Issues are sorted by program and node.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let subroutine_starts: Vec<AbsoluteNodeId> = self
+            .programs
+            .values()
+            .flat_map(|program| program.nodes.values())
+            .filter(|node| node.class.1 == "subroutine")
+            .filter_map(|node| node.variant.parse::<Subroutine>().ok())
+            .map(|subroutine| subroutine.start().clone())
+            .collect();
+        let mut unreachable = vec![];
+        for (program_id, program) in &self.programs {
+            let roots = program
+                .nodes
+                .iter()
+                .filter(|(_, node)| node.class.1 == "start")
+                .map(|(node_id, _)| *node_id)
+                .chain(
+                    subroutine_starts
+                        .iter()
+                        .filter(|start| &start.0 == program_id)
+                        .map(|start| start.1),
+                );
+            let reachable = program.reachable_nodes(roots);
+            unreachable.extend(
+                program
+                    .nodes
+                    .keys()
+                    .filter(|node_id| !reachable.contains(node_id))
+                    .map(|node_id| AbsoluteNodeId(program_id.clone(), *node_id)),
+            );
+        }
+        unreachable.sort_by_key(|node| (node.0.to_string(), node.1));
+        unreachable
+            .into_iter()
+            .map(ValidationIssue::UnreachableNode)
+            .collect()
+    }
+}
+
+/// Possible mistake in a program that doesn't prevent it from loading
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ValidationIssue {
+    #[error("Node {0} is not reachable from any start node")]
+    UnreachableNode(AbsoluteNodeId),
+}
+
+/// Finds and reads programs by their import path
+pub trait ImportResolver {
+    fn read_import(&mut self, path: &ProgramId) -> Result<Program, LoadError>;
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum ImportError {
+    #[error("cyclic import: {}", .0.iter().map(ToString::to_string).collect::<Vec<String>>().join(" -> "))]
+    Cyclic(Vec<ProgramId>),
+    #[error("Invalid import path: {0}")]
+    InvalidPath(ModulePathParseError),
+    #[error("Failed to read import: {0}")]
+    Read(LoadError),
+}
+
+impl From<LoadError> for ImportError {
+    fn from(e: LoadError) -> Self {
+        Self::Read(e)
+    }
+}
+
+impl From<ModulePathParseError> for ImportError {
+    fn from(e: ModulePathParseError) -> Self {
+        Self::InvalidPath(e)
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum LoadError {
+    #[error("Node {node} uses class {class} which is not loaded")]
+    UnknownClass {
+        node: AbsoluteNodeId,
+        class: ModulePath,
+    },
+    #[error("Class {0} is already defined")]
+    DuplicateClass(ModulePath),
+    #[error("Program {0} not found")]
+    ProgramNotFound(ProgramId),
+    #[error("Node {node}: {error}")]
+    InvalidVariant {
+        node: AbsoluteNodeId,
+        error: VariantError,
+    },
+    #[error("Node {node} uses node {idx} of class {class}, which has no such node")]
+    InvalidNodeIndex {
+        node: AbsoluteNodeId,
+        class: ModulePath,
+        idx: usize,
+    },
+    #[error("Node {node} uses a class that is loaded with the name {name}")]
+    ClassMismatch { node: AbsoluteNodeId, name: String },
+    /// Method nodes of a class have to be placed in the program and be of a class that is not
+    /// defined by the program
+    #[error("Class {class} uses node {node} as a method, but it can't be placed before the class")]
+    MissingMethodNode {
+        class: ModulePath,
+        node: AbsoluteNodeId,
+    },
+    /// A program file could not be read. `line` and `column` are known for text formats.
+    #[error("error in {path}{}: {message}", line.map(|line| format!(" at line {line}")).unwrap_or_default())]
+    Deserialize {
+        path: String,
+        line: Option<usize>,
+        column: Option<usize>,
+        message: String,
+    },
+}
+
+/// A change to a loaded program that could not be applied
+#[derive(Debug, Clone, Error)]
+pub enum PatchError {
+    #[error("Program {0} is not loaded")]
+    UnknownProgram(ProgramId),
+    #[error("Class {0} is not loaded")]
+    UnknownClass(ModulePath),
+    #[error(transparent)]
+    InvalidVariant(#[from] VariantError),
+    #[error("Class {class} has no node {idx}")]
+    InvalidNodeIndex { class: ModulePath, idx: usize },
+    #[error("Class {class} is loaded with the name {name}")]
+    ClassMismatch { class: ModulePath, name: String },
+    #[error("Invalid const value {value:?} of input {input} of node {node}: {message}")]
+    InvalidConstInput {
+        node: NodeId,
+        input: usize,
+        value: String,
+        message: String,
+    },
+}
+
+impl LoadError {
+    /// Error of placing a node while its program is loaded
+    fn placing(node: AbsoluteNodeId, error: PatchError) -> Self {
+        match error {
+            PatchError::InvalidVariant(error) => Self::InvalidVariant { node, error },
+            PatchError::InvalidNodeIndex { class, idx } => {
+                Self::InvalidNodeIndex { node, class, idx }
+            }
+            PatchError::ClassMismatch { name, .. } => Self::ClassMismatch { node, name },
+            error => unreachable!("placing a node failed with {error}"),
+        }
+    }
+}
+
 /// A program that contains nodes, classes, constant objects, etc.
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct Program {
@@ -241,12 +698,536 @@ pub struct Program {
     pub nodes: HashMap<NodeId, NodeInfo>,
     /// Used for setting the position in a graphical view of the program. Third value is z-index.
     pub node_positions: Option<HashMap<NodeId, (f32, f32, f32)>>,
+    /// Labels, comments and other editor data attached to nodes
+    #[serde(default)]
+    pub node_metadata: Option<HashMap<NodeId, NodeMetadata>>,
     /// All classes defined in a program
     pub classes: Vec<ProtoClass>,
     /// Execution order connections between nodes
     pub branch_edges: HashMap<NodeBranchId, NodeId>,
+    /// Where execution continues when a branch of a node has no edge, instead of ending
+    #[serde(default)]
+    pub fallback_edges: Option<HashMap<NodeId, NodeId>>,
     /// Data connections between nodes
     pub connections: HashSet<Connection>,
     /// COnstant inputs that are not getting a value through a connection
     pub const_inputs: HashMap<InputSocketId, String>,
 }
+
+/// Bytes that start bincode files of programs in the current layout of [`Program`]. Bincode
+/// doesn't store field names, so files without the header are read with the layout of
+/// [`LegacyProgram`].
+pub const BINCODE_HEADER: &[u8] = b"SSC\x01";
+
+/// Layout of [`Program`] before node metadata and fallback edges were added
+#[derive(Debug, Clone, Deserialize)]
+pub struct LegacyProgram {
+    pub imports: Option<Vec<String>>,
+    pub nodes: HashMap<NodeId, NodeInfo>,
+    pub node_positions: Option<HashMap<NodeId, (f32, f32, f32)>>,
+    pub classes: Vec<ProtoClass>,
+    pub branch_edges: HashMap<NodeBranchId, NodeId>,
+    pub connections: HashSet<Connection>,
+    pub const_inputs: HashMap<InputSocketId, String>,
+}
+
+impl From<LegacyProgram> for Program {
+    fn from(program: LegacyProgram) -> Self {
+        Self {
+            imports: program.imports,
+            nodes: program.nodes,
+            node_positions: program.node_positions,
+            node_metadata: None,
+            classes: program.classes,
+            branch_edges: program.branch_edges,
+            fallback_edges: None,
+            connections: program.connections,
+            const_inputs: program.const_inputs,
+        }
+    }
+}
+
+impl Program {
+    /// Nodes that execution can get to from the roots by following branch and fallback edges,
+    /// including the roots themselves
+    pub fn reachable_nodes(&self, roots: impl IntoIterator<Item = NodeId>) -> HashSet<NodeId> {
+        let mut next_nodes: HashMap<NodeId, Vec<NodeId>> = HashMap::new();
+        let edges = self
+            .branch_edges
+            .iter()
+            .map(|(branch, next)| (branch.0, *next))
+            .chain(
+                self.fallback_edges
+                    .iter()
+                    .flatten()
+                    .map(|(n, next)| (*n, *next)),
+            );
+        for (node, next) in edges {
+            next_nodes.entry(node).or_default().push(next);
+        }
+        let mut reachable = HashSet::new();
+        let mut queue: Vec<NodeId> = roots.into_iter().collect();
+        while let Some(node) = queue.pop() {
+            if reachable.insert(node) {
+                queue.extend(next_nodes.get(&node).into_iter().flatten());
+            }
+        }
+        reachable
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ImportError, ImportResolver, LoadError, PatchError, Program, ProgramCollection,
+        ValidationIssue,
+    };
+    use crate::{
+        class::{Class, ProtoClass},
+        module::ModulePath,
+        node::{AbsoluteNodeId, Node, NodeBranchId, NodeInfo, NodeMetadata},
+        socket::{Connection, InputSocket, InputSocketId, OutputSocket, OutputSocketId, SocketId},
+        stdlib::{number_class, StdPlugin},
+        test_utils::{main_path, TestProgram},
+        ExecutionContext, ExecutionError, Executor, Plugin,
+    };
+    use std::{borrow::Cow, collections::HashMap, rc::Rc};
+
+    /// Outputs its number input, which has a default
+    #[derive(Debug, Clone)]
+    struct DefaultedNode(&'static str);
+
+    fn defaulted_class(default: &'static str) -> Class {
+        Class {
+            name: "defaulted".into(),
+            nodes: vec![Rc::new(DefaultedNode(default)) as Rc<dyn Node>],
+            obj_from_str: None,
+        }
+    }
+
+    impl Node for DefaultedNode {
+        fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+            let value = context.get_object(0)?;
+            context.set_outputs(vec![value]);
+            Ok(0)
+        }
+
+        fn class(&self) -> Class {
+            defaulted_class(self.0)
+        }
+
+        fn variants(&self) -> Vec<Cow<'_, str>> {
+            vec!["defaulted".into()]
+        }
+
+        fn current_variant(&self) -> Cow<'_, str> {
+            "defaulted".into()
+        }
+
+        fn set_variant(&mut self, _variant: &str) {}
+
+        fn inputs(&self) -> Vec<InputSocket> {
+            vec![InputSocket {
+                class: number_class(),
+                default: Some(self.0.into()),
+            }]
+        }
+
+        fn outputs(&self) -> Vec<OutputSocket> {
+            vec![OutputSocket {
+                class: number_class(),
+            }]
+        }
+
+        fn clone_node(&self) -> Rc<dyn Node> {
+            Rc::new(self.clone())
+        }
+    }
+
+    struct DefaultedPlugin(&'static str);
+
+    impl Plugin for DefaultedPlugin {
+        fn classes(&self) -> HashMap<ModulePath, Class> {
+            [(
+                ModulePath(vec!["test".into()], "defaulted".into()),
+                defaulted_class(self.0),
+            )]
+            .into()
+        }
+    }
+
+    /// Load a program that ends with the output of a node relying on its input default
+    fn load_defaulted(default: &'static str) -> Result<Executor, LoadError> {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node_of(
+                1,
+                ModulePath(vec!["test".into()], "defaulted".into()),
+                0,
+                "",
+            )
+            .end(2, 1)
+            .chain(&[0, 1, 2])
+            .connect(1, 0, 2, 0);
+        let mut executor = Executor::default();
+        executor.load_plugin(StdPlugin)?;
+        executor.load_plugin(DefaultedPlugin(default))?;
+        executor.load_program(program.0, main_path())?;
+        Ok(executor)
+    }
+
+    #[test]
+    fn input_default_is_used() {
+        let mut executor = load_defaulted("4").unwrap();
+        executor.start_execution(true).unwrap();
+        assert_eq!(executor.final_output().unwrap()[0].as_number(), 4.0);
+    }
+
+    #[test]
+    fn invalid_default_fails_to_load() {
+        assert!(matches!(
+            load_defaulted("four"),
+            Err(LoadError::InvalidVariant { .. })
+        ));
+    }
+
+    fn connection(from: u32, to: u32) -> Connection {
+        Connection {
+            output: OutputSocketId(SocketId(from, 0)),
+            input: InputSocketId(SocketId(to, 0)),
+        }
+    }
+
+    #[test]
+    fn patched_program_runs() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "string", "a")
+            .end(9, 1)
+            .chain(&[0, 1, 9])
+            .connect(1, 0, 9, 0);
+        let mut executor = program.executor();
+        executor.start_execution(true).unwrap();
+        assert_eq!(executor.final_output().unwrap()[0].to_string(), "a");
+
+        let main = main_path();
+        executor
+            .upsert_node(
+                AbsoluteNodeId(main.clone(), 2),
+                NodeInfo {
+                    class: ModulePath(vec!["std".into()], "cast".into()),
+                    idx: 0,
+                    variant: "cast:std.string".into(),
+                },
+            )
+            .unwrap();
+        executor
+            .set_const_input(&main, InputSocketId(SocketId(2, 0)), "b".into())
+            .unwrap();
+        executor
+            .set_branch_edge(&main, NodeBranchId(1, 0), 2)
+            .unwrap();
+        executor
+            .set_branch_edge(&main, NodeBranchId(2, 0), 9)
+            .unwrap();
+        executor.remove_connection(&main, &connection(1, 9));
+        executor.add_connection(&main, connection(2, 9)).unwrap();
+        executor.start_execution(true).unwrap();
+        assert_eq!(executor.final_output().unwrap()[0].to_string(), "b");
+    }
+
+    #[test]
+    fn patching_unknown_program_fails() {
+        let mut executor = TestProgram::new().executor();
+        let other = ModulePath(vec![], "other".into());
+        assert!(matches!(
+            executor.set_branch_edge(&other, NodeBranchId(0, 0), 1),
+            Err(PatchError::UnknownProgram(_))
+        ));
+        assert!(matches!(
+            executor.add_connection(&other, connection(0, 1)),
+            Err(PatchError::UnknownProgram(_))
+        ));
+        assert!(matches!(
+            executor.upsert_node(
+                AbsoluteNodeId(main_path(), 0),
+                NodeInfo {
+                    class: ModulePath(vec![], "missing".into()),
+                    idx: 0,
+                    variant: String::new(),
+                },
+            ),
+            Err(PatchError::UnknownClass(_))
+        ));
+    }
+
+    #[test]
+    fn patching_in_bad_node_index_fails() {
+        let mut program = TestProgram::new();
+        program.start(0).end(9, 0).chain(&[0, 9]);
+        let mut executor = program.executor();
+        let print = ModulePath(vec!["std".into()], "print".into());
+        assert!(matches!(
+            executor.upsert_node(
+                AbsoluteNodeId(main_path(), 1),
+                NodeInfo {
+                    class: print.clone(),
+                    idx: 5,
+                    variant: "print".into(),
+                },
+            ),
+            Err(PatchError::InvalidNodeIndex { class, idx: 5 }) if class == print
+        ));
+        executor.start_execution(true).unwrap();
+    }
+
+    #[test]
+    fn node_metadata_round_trip() {
+        let mut program = TestProgram::new();
+        program.start(0).end(1, 0).chain(&[0, 1]);
+        let metadata = NodeMetadata {
+            label: Some("Entry".into()),
+            comment: Some("Runs first".into()),
+            color: Some("#ff0000".into()),
+        };
+        program.0.node_metadata = Some([(0, metadata.clone())].into());
+        let text = ron::to_string(&program.0).unwrap();
+        let loaded: Program = ron::from_str(&text).unwrap();
+        assert_eq!(loaded.node_metadata, Some([(0, metadata)].into()));
+        program.0 = loaded;
+        program.run();
+    }
+
+    #[test]
+    fn program_without_metadata_loads() {
+        let program: Program =
+            ron::from_str(include_str!("../example_programs/hello_world.ron.ssc")).unwrap();
+        assert_eq!(program.node_metadata, None);
+    }
+
+    #[cfg(feature = "format-bincode")]
+    #[test]
+    fn legacy_bincode_layout() {
+        let bytes = include_bytes!("../example_programs/hello_world.bin.ssc");
+        let legacy: super::LegacyProgram = bincode::deserialize(bytes).unwrap();
+        let program = Program::from(legacy);
+        let text: Program =
+            ron::from_str(include_str!("../example_programs/hello_world.ron.ssc")).unwrap();
+        assert_eq!(program.nodes.len(), text.nodes.len());
+        assert_eq!(program.const_inputs, text.const_inputs);
+    }
+
+    #[test]
+    fn unknown_class_fails_to_load() {
+        let mut program = TestProgram::new();
+        program.start(0).node(1, "missing", "missing");
+        let mut executor = Executor::default();
+        executor.load_plugin(StdPlugin).unwrap();
+        assert!(matches!(
+            executor.load_program(program.0, main_path()),
+            Err(LoadError::UnknownClass { class, .. }) if class.1 == "missing"
+        ));
+    }
+
+    #[test]
+    fn bad_node_index_fails_to_load() {
+        let mut program = TestProgram::new();
+        program.start(0).node_of(
+            1,
+            ModulePath(vec!["std".into()], "print".into()),
+            5,
+            "print",
+        );
+        let mut executor = Executor::default();
+        executor.load_plugin(StdPlugin).unwrap();
+        assert!(matches!(
+            executor.load_program(program.0, main_path()),
+            Err(LoadError::InvalidNodeIndex { node, class, idx: 5 }) if node.1 == 1 && class.1 == "print"
+        ));
+    }
+
+    #[test]
+    fn nodes_of_program_classes_are_placed_after_the_class() {
+        let point = ModulePath(vec![], "Point".into());
+        let mut program = TestProgram::new();
+        program.0.classes.push(ProtoClass {
+            name: "Point".into(),
+            nodes: vec![],
+        });
+        program.start(0).node_of(1, point.clone(), 0, "");
+        let mut executor = Executor::default();
+        executor.load_plugin(StdPlugin).unwrap();
+        assert!(matches!(
+            executor.load_program(program.0.clone(), main_path()),
+            Err(LoadError::InvalidNodeIndex { node, class, idx: 0 }) if node.1 == 1 && class == point
+        ));
+
+        // A node of the class copies the method node at its index
+        program.0.classes[0].nodes.push(2);
+        program.node(2, "nop", "nop");
+        executor
+            .load_program(program.0.clone(), main_path())
+            .unwrap();
+        let node = executor
+            .loaded
+            .get_node(&AbsoluteNodeId(main_path(), 1))
+            .unwrap();
+        assert_eq!(node.class().name, "nop");
+
+        // A method node can't be a node of the class itself
+        program.0.classes[0].nodes = vec![1];
+        let mut executor = Executor::default();
+        executor.load_plugin(StdPlugin).unwrap();
+        assert!(matches!(
+            executor.load_program(program.0, main_path()),
+            Err(LoadError::MissingMethodNode { node, .. }) if node.1 == 1
+        ));
+    }
+
+    #[test]
+    fn duplicate_class_fails_to_load() {
+        let mut executor = TestProgram::new().executor();
+        assert!(matches!(
+            executor.load_plugin(StdPlugin),
+            Err(LoadError::DuplicateClass(_))
+        ));
+        let mut program = TestProgram::new();
+        program.0.classes.push(ProtoClass {
+            name: "Point".into(),
+            nodes: vec![],
+        });
+        executor
+            .load_program(program.0.clone(), ModulePath(vec![], "a".into()))
+            .unwrap();
+        assert!(matches!(
+            executor.load_program(program.0, ModulePath(vec![], "b".into())),
+            Err(LoadError::DuplicateClass(class)) if class.1 == "Point"
+        ));
+    }
+
+    #[test]
+    fn invalid_variant_fails_to_load() {
+        let mut program = TestProgram::new();
+        program.0.classes.push(ProtoClass {
+            name: "Point".into(),
+            nodes: vec![],
+        });
+        program
+            .start(0)
+            .node(1, "print", "print:sideways")
+            .end(2, 0)
+            .chain(&[0, 1, 2]);
+        let mut executor = TestProgram::new().executor();
+        let path = ModulePath(vec![], "a".into());
+        assert!(matches!(
+            executor.load_program(program.0.clone(), path.clone()),
+            Err(LoadError::InvalidVariant { node, .. }) if node.1 == 1
+        ));
+        // Classes of the failed program are not left behind, so the fixed program loads
+        program.node(1, "print", "print");
+        executor.load_program(program.0, path).unwrap();
+    }
+
+    /// If node whose true branch is not connected, false branch ends with "false"
+    fn dead_end_if() -> TestProgram {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "if", "if")
+            .const_input(1, 0, "true")
+            .value(2, "string", "false")
+            .value(3, "string", "continued")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .edge(3, 0, 9)
+            .connect(2, 0, 9, 0)
+            .connect(3, 0, 9, 0);
+        program
+    }
+
+    #[test]
+    fn unconnected_branch_ends_execution() {
+        // Without an end node, execution finishes with no values
+        let mut executor = dead_end_if().executor();
+        executor.start_execution(true).unwrap();
+        assert!(executor.final_output().unwrap().is_empty());
+    }
+
+    #[test]
+    fn unconnected_branch_falls_through() {
+        let mut program = dead_end_if();
+        program.0.fallback_edges = Some([(1, 3)].into());
+        assert_eq!(program.run_strings(), ["continued"]);
+    }
+
+    /// Reads imports from programs given by path
+    struct MapResolver(HashMap<&'static str, Vec<&'static str>>);
+
+    impl ImportResolver for MapResolver {
+        fn read_import(&mut self, path: &ModulePath) -> Result<Program, LoadError> {
+            let imports = self
+                .0
+                .get(path.to_string().as_str())
+                .ok_or_else(|| LoadError::ProgramNotFound(path.clone()))?;
+            Ok(Program {
+                imports: Some(imports.iter().map(|import| import.to_string()).collect()),
+                ..Default::default()
+            })
+        }
+    }
+
+    fn resolve(imports: &[(&'static str, Vec<&'static str>)]) -> Result<(), ImportError> {
+        let main = Program {
+            imports: Some(vec!["a".into()]),
+            ..Default::default()
+        };
+        ProgramCollection::default().resolve_imports(
+            main_path(),
+            &main,
+            &mut MapResolver(imports.iter().cloned().collect()),
+        )
+    }
+
+    #[test]
+    fn cyclic_imports() {
+        let error = resolve(&[("a", vec!["b"]), ("b", vec!["a"])]).unwrap_err();
+        assert_eq!(error.to_string(), "cyclic import: __main__ -> a -> b -> a");
+    }
+
+    #[test]
+    fn missing_import() {
+        assert!(resolve(&[("a", vec![])]).is_ok());
+        assert!(matches!(
+            resolve(&[("a", vec!["b"])]),
+            Err(ImportError::Read(LoadError::ProgramNotFound(_)))
+        ));
+    }
+
+    #[test]
+    fn orphaned_node_is_unreachable() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "subroutine", "subroutine:__main__@20:__main__@22")
+            .end(9, 0)
+            .chain(&[0, 1, 9])
+            .value(5, "string", "orphan")
+            .edge(5, 0, 9)
+            .node(20, "start", "start#sub#[]")
+            .value(21, "string", "called")
+            .end(22, 0)
+            .chain(&[20, 21, 22]);
+        let programs = ProgramCollection {
+            programs: [(main_path(), program.0)].into(),
+        };
+        assert_eq!(
+            programs.validate(),
+            [ValidationIssue::UnreachableNode(AbsoluteNodeId(
+                main_path(),
+                5
+            ))]
+        );
+    }
+}
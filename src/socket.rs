@@ -50,6 +50,18 @@ pub struct InputSocket {
     /// This is merely a type suggestion used to hint what type is expected. Can be used by IDEs to
     /// force only certain type in a connection, requiring to do a proper conversion.
     pub class: Class,
+    /// Value used when the input is not supplied by a connection or a const input. Converted to
+    /// an object with the class's `obj_from_str`.
+    pub default: Option<String>,
+}
+
+/// Sockets without a default are serialized as just the class, so that older variants (such as
+/// the ones of start and end nodes) stay valid
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum InputSocketRepr {
+    Class(Class),
+    WithDefault(Class, Option<String>),
 }
 
 impl Serialize for InputSocket {
@@ -57,7 +69,13 @@ impl Serialize for InputSocket {
     where
         S: serde::Serializer,
     {
-        self.class.serialize(serializer)
+        match &self.default {
+            None => self.class.serialize(serializer),
+            Some(default) => {
+                InputSocketRepr::WithDefault(self.class.clone(), Some(default.clone()))
+                    .serialize(serializer)
+            }
+        }
     }
 }
 
@@ -66,8 +84,13 @@ impl<'de> Deserialize<'de> for InputSocket {
     where
         D: serde::Deserializer<'de>,
     {
-        let class = Class::deserialize(deserializer)?;
-        Ok(Self { class })
+        Ok(match InputSocketRepr::deserialize(deserializer)? {
+            InputSocketRepr::Class(class) => Self {
+                class,
+                default: None,
+            },
+            InputSocketRepr::WithDefault(class, default) => Self { class, default },
+        })
     }
 }
 
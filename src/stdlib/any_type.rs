@@ -1,7 +1,7 @@
 use crate::object::{ObjectEq, ObjectOrd, ObjectPartialEq, ObjectPartialOrd};
 use stainless_script_derive::{ObjectEq, ObjectOrd, ObjectPartialEq, ObjectPartialOrd};
 
-use super::NopNode;
+use super::{cached_class, NopNode};
 use crate::{
     class::Class,
     node::Node,
@@ -10,11 +10,11 @@ use crate::{
 use std::{fmt::Display, rc::Rc, str::FromStr};
 
 pub fn any_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "any".into(),
         nodes: vec![Rc::new(NopNode) as Rc<dyn Node>],
         obj_from_str: Some(<AnyType as ObjectFromStr>::from_str),
-    }
+    })
 }
 
 #[derive(
@@ -41,8 +41,9 @@ impl Object for AnyType {
         any_class()
     }
 
+    /// NaN if the text is not a number
     fn as_number(&self) -> f64 {
-        self.0.parse().unwrap()
+        self.0.parse().unwrap_or(f64::NAN)
     }
 
     fn as_bool(&self) -> bool {
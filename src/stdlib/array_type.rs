@@ -1,39 +1,173 @@
-use super::{any_class, number_class, AnyType};
+use super::{
+    any_class, cached_class, dict_class, number_class, ron_text, subroutine_class, Dict, DictVal,
+    NullType, Subroutine,
+};
 use crate::{
     class::Class,
-    node::Node,
-    object::{Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd},
+    module::ModulePath,
+    node::{Node, VariantError},
+    object::{
+        class_hash, Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd,
+    },
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
-use stainless_script_derive::{ObjectEq, ObjectOrd};
+use stainless_script_derive::ObjectEq;
 use std::{fmt::Display, rc::Rc, str::FromStr};
+use thiserror::Error;
 
 pub fn array_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "array".into(),
         nodes: vec![Rc::new(ArrayConstructor(1)) as Rc<dyn Node>],
         obj_from_str: Some(<Array as ObjectFromStr>::from_str),
-    }
+    })
+}
+
+pub fn range_class() -> Class {
+    cached_class(|| Class {
+        name: "range".into(),
+        nodes: vec![Rc::new(RangeNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn destructure_class() -> Class {
+    cached_class(|| Class {
+        name: "destructure".into(),
+        nodes: vec![Rc::new(DestructureNode(2)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn array_stats_class() -> Class {
+    cached_class(|| Class {
+        name: "array_stats".into(),
+        nodes: vec![Rc::new(ArrayStatsNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn pack_class() -> Class {
+    cached_class(|| Class {
+        name: "pack".into(),
+        nodes: vec![Rc::new(PackNode(vec![])) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn unpack_class() -> Class {
+    cached_class(|| Class {
+        name: "unpack".into(),
+        nodes: vec![Rc::new(UnpackNode(vec![])) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn enumerate_class() -> Class {
+    cached_class(|| Class {
+        name: "enumerate".into(),
+        nodes: vec![Rc::new(EnumerateNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn max_len_class() -> Class {
+    cached_class(|| Class {
+        name: "max_len".into(),
+        nodes: vec![Rc::new(MaxLenNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
 }
 
-#[derive(Debug, Clone, ObjectEq, ObjectOrd)]
+pub fn choice_class() -> Class {
+    cached_class(|| Class {
+        name: "choice".into(),
+        nodes: vec![Rc::new(ChoiceNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn shuffle_class() -> Class {
+    cached_class(|| Class {
+        name: "shuffle".into(),
+        nodes: vec![Rc::new(ShuffleNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn unique_class() -> Class {
+    cached_class(|| Class {
+        name: "unique".into(),
+        nodes: vec![Rc::new(UniqueNode { sorted: false }) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn vector_op_class() -> Class {
+    cached_class(|| Class {
+        name: "vector_op".into(),
+        nodes: vec![Rc::new(VectorOpNode(VectorOp::Dot)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn find_index_class() -> Class {
+    cached_class(|| Class {
+        name: "find_index".into(),
+        nodes: vec![Rc::new(ArrayFindIndexNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn find_class() -> Class {
+    cached_class(|| Class {
+        name: "find".into(),
+        nodes: vec![Rc::new(ArrayFindNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn flatten_class() -> Class {
+    cached_class(|| Class {
+        name: "flatten".into(),
+        nodes: vec![Rc::new(FlattenNode(FlattenDepth::One)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+#[derive(Debug, Clone, ObjectEq)]
 pub struct Array(pub(crate) Vec<Rc<dyn Object>>);
 
+/// Collect items of an array object through its fields
+pub(crate) fn array_items(array: &Rc<dyn Object>) -> Vec<Rc<dyn Object>> {
+    let len = array
+        .get_field(Rc::new("len".to_string()) as Rc<dyn Object>)
+        .as_number() as usize;
+    (0..len)
+        .map(|i| array.get_field(Rc::new(i as f64) as Rc<dyn Object>))
+        .collect()
+}
+
+/// Format: RON sequence of items, `[1, "a"]`
 impl FromStr for Array {
-    type Err = <AnyType as FromStr>::Err;
+    type Err = ArrayParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        assert_eq!(&s[0..2], "[");
-        assert_eq!(&s[s.len() - 1..s.len()], "]");
-        let items: Result<Vec<Rc<dyn Object>>, Self::Err> = s[1..s.len() - 1]
-            .split(',')
-            .map(|s| {
-                let trimmed = s.trim();
-                Ok(Rc::new(trimmed.parse::<AnyType>()?) as Rc<dyn Object>)
-            })
-            .collect();
-        items.map(Array)
+        let items = ron::from_str::<Vec<ron::Value>>(s)?;
+        Ok(DictVal::array_from_seq(&items))
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum ArrayParseError {
+    #[error("{0}")]
+    DeserializingError(ron::error::SpannedError),
+}
+
+impl From<ron::error::SpannedError> for ArrayParseError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        Self::DeserializingError(value)
     }
 }
 
@@ -75,6 +209,24 @@ impl Object for Array {
         }
     }
 
+    fn clone_object(&self) -> Rc<dyn Object> {
+        Rc::new(self.clone())
+    }
+
+    /// Strings are quoted, so that the text parses back
+    fn serialize_with_class(&self) -> (ModulePath, String) {
+        (ModulePath(vec![], self.class().name), ron_text(self))
+    }
+
+    /// Hashes of the items are combined, so arrays of equal items hash the same
+    fn object_hash(&self) -> u64 {
+        let items = self
+            .0
+            .iter()
+            .flat_map(|item| item.object_hash().to_le_bytes());
+        class_hash("array", items)
+    }
+
     fn set_field(&mut self, field: Rc<dyn Object>, value: Rc<dyn Object>) {
         if field.class() == number_class() {
             self.0[field.as_number() as usize] = value;
@@ -84,34 +236,50 @@ impl Object for Array {
 
 impl ObjectPartialEq for Array {
     fn eq(&self, other: Rc<dyn Object>) -> bool {
-        if other.class() == self.class() {
-            if other
-                .get_field(Rc::new("len".to_string()) as Rc<dyn Object>)
-                .as_number() as usize
-                == self.0.len()
-            {
-                let other = &other as &dyn std::any::Any;
-                if let Some(other) = other.downcast_ref::<Self>() {
-                    self.0
-                        .iter()
-                        .zip(other.0.iter())
-                        .all(|(l, r)| l.eq(Rc::clone(r)))
-                } else {
-                    false
-                }
-            } else {
-                false
+        other.downcast_ref::<Self>().is_some_and(|other| {
+            self.0.len() == other.0.len()
+                && self
+                    .0
+                    .iter()
+                    .zip(other.0.iter())
+                    .all(|(l, r)| l.eq(Rc::clone(r)))
+        })
+    }
+}
+
+impl ObjectPartialOrd for Array {
+    /// Lexicographical order of the items. Arrays with items of different classes at the same
+    /// position can't be ordered.
+    fn partial_cmp(&self, other: Rc<dyn Object>) -> Option<std::cmp::Ordering> {
+        let other = other.downcast_ref::<Self>()?;
+        for (l, r) in self.0.iter().zip(other.0.iter()) {
+            if l.class() != r.class() {
+                return None;
+            }
+            match l.partial_cmp(Rc::clone(r))? {
+                std::cmp::Ordering::Equal => continue,
+                ordering => return Some(ordering),
             }
-        } else {
-            false
         }
+        Some(self.0.len().cmp(&other.0.len()))
     }
 }
 
-impl ObjectPartialOrd for Array {
-    /// UNIMPLEMENTED, WILL PANIC, DO NOT USE AS DICT KEY
-    fn partial_cmp(&self, _other: Rc<dyn Object>) -> Option<std::cmp::Ordering> {
-        todo!()
+/// Total order for arrays used as dict keys or set items. Unlike `partial_cmp`, items of different
+/// classes are ordered by class name, the same way as dict keys.
+impl ObjectOrd for Array {
+    fn cmp(&self, other: Rc<dyn Object>) -> std::cmp::Ordering {
+        let Some(other) = other.downcast_ref::<Self>() else {
+            return Ord::cmp("array", other.class().name.as_str());
+        };
+        let items = |array: &Self| {
+            array
+                .0
+                .iter()
+                .map(|item| DictVal(Rc::clone(item)))
+                .collect::<Vec<_>>()
+        };
+        Ord::cmp(&items(self), &items(other))
     }
 }
 
@@ -119,10 +287,10 @@ impl ObjectPartialOrd for Array {
 pub struct ArrayConstructor(usize);
 
 impl Node for ArrayConstructor {
-    fn execute(&self, context: &mut ExecutionContext) -> usize {
-        let items = context.get_inputs();
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let items = context.get_inputs()?;
         context.set_outputs(vec![Rc::new(Array(items)) as Rc<dyn Object>]);
-        0
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -138,7 +306,16 @@ impl Node for ArrayConstructor {
     }
 
     fn set_variant(&mut self, variant: &str) {
-        self.0 = variant.strip_prefix("array-").unwrap().parse().unwrap()
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant
+            .strip_prefix("array-")
+            .ok_or_else(|| VariantError::new(variant, "expected `array-` prefix"))?
+            .parse()
+            .map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
     }
 
     fn accepts_arbitrary_variants(&self) -> bool {
@@ -146,7 +323,290 @@ impl Node for ArrayConstructor {
     }
 
     fn inputs(&self) -> Vec<InputSocket> {
-        vec![InputSocket { class: any_class() }; self.0]
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            self.0
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: array_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Most items a range can have
+const MAX_RANGE_LEN: f64 = 10_000_000.0;
+
+/// Array of numbers from start (inclusive) to end (exclusive), with an optional step that
+/// defaults to 1. Negative step counts down. Branches to 1 if step is 0, any of the numbers is
+/// not finite or the range would have more than 10 million items.
+#[derive(Debug, Clone)]
+pub struct RangeNode;
+
+impl Node for RangeNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (start, end, step) = (
+            context.get_number(0)?,
+            context.get_number(1)?,
+            context.get_number(2)?,
+        );
+        if step == 0.0 || ![start, end, step].iter().all(|n| n.is_finite()) {
+            return Ok(1);
+        }
+        let len = ((end - start) / step).ceil().max(0.0);
+        if len > MAX_RANGE_LEN {
+            return Ok(1);
+        }
+        let items = (0..len as usize)
+            .map(|i| Rc::new(start + i as f64 * step) as Rc<dyn Object>)
+            .collect();
+        context.set_outputs(vec![Rc::new(Array(items)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        range_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["range".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "range".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: number_class(),
+                default: None,
+            },
+            InputSocket {
+                class: number_class(),
+                default: None,
+            },
+            InputSocket {
+                class: number_class(),
+                default: Some("1".into()),
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: array_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Pair every element of an array with its index: `[[0, a], [1, b], ...]`
+#[derive(Debug, Clone)]
+pub struct EnumerateNode;
+
+impl Node for EnumerateNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let items = array_items(&context.get_object(0)?);
+        let pairs = items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                Rc::new(Array(vec![Rc::new(i as f64) as Rc<dyn Object>, item])) as Rc<dyn Object>
+            })
+            .collect();
+        context.set_outputs(vec![Rc::new(Array(pairs)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        enumerate_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["enumerate".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "enumerate".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: array_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Length of the longest element of an array, 0 if it's empty. Arrays are measured by element
+/// count, other elements by char count of their string form. Useful for aligning columns.
+#[derive(Debug, Clone)]
+pub struct MaxLenNode;
+
+impl Node for MaxLenNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = array_items(&context.get_object(0)?)
+            .iter()
+            .map(|item| {
+                if item.class() == array_class() {
+                    array_items(item).len()
+                } else {
+                    item.as_string().chars().count()
+                }
+            })
+            .max()
+            .unwrap_or(0);
+        context.set_outputs(vec![Rc::new(res as f64) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        max_len_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["max_len".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "max_len".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Random element of an array, drawn from the executor's random number generator. Branches to 1
+/// if the array is empty.
+#[derive(Debug, Clone)]
+pub struct ChoiceNode;
+
+impl Node for ChoiceNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let items = array_items(&context.get_object(0)?);
+        if items.is_empty() {
+            return Ok(1);
+        }
+        let idx = (context.random_u64() % items.len() as u64) as usize;
+        context.set_outputs(vec![Rc::clone(&items[idx])]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        choice_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["choice".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "choice".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Elements of an array in random order, drawn from the executor's random number generator
+#[derive(Debug, Clone)]
+pub struct ShuffleNode;
+
+impl Node for ShuffleNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let mut items = array_items(&context.get_object(0)?);
+        for i in (1..items.len()).rev() {
+            let j = (context.random_u64() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+        context.set_outputs(vec![Rc::new(Array(items)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        shuffle_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["shuffle".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "shuffle".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
     }
 
     fn outputs(&self) -> Vec<OutputSocket> {
@@ -159,3 +619,1032 @@ impl Node for ArrayConstructor {
         Rc::new(self.clone()) as Rc<dyn Node>
     }
 }
+
+/// Remove repeated elements of an array, keeping the first occurrence of each. Elements are equal
+/// if they are of the same class and `ObjectPartialEq` says so. The `sorted` variant only compares
+/// neighbouring elements, which is faster but only removes all duplicates if the array is sorted.
+#[derive(Debug, Clone)]
+pub struct UniqueNode {
+    sorted: bool,
+}
+
+impl Node for UniqueNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let same =
+            |a: &Rc<dyn Object>, b: &Rc<dyn Object>| a.class() == b.class() && a.eq(Rc::clone(b));
+        let mut res: Vec<Rc<dyn Object>> = vec![];
+        for item in array_items(&context.get_object(0)?) {
+            let seen = if self.sorted {
+                res.last().is_some_and(|last| same(last, &item))
+            } else {
+                res.iter().any(|other| same(other, &item))
+            };
+            if !seen {
+                res.push(item)
+            }
+        }
+        context.set_outputs(vec![Rc::new(Array(res)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        unique_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["unique".into(), "sorted".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        if self.sorted { "sorted" } else { "unique" }.into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.sorted = match variant {
+            "unique" => false,
+            "sorted" => true,
+            _ => return Err(VariantError::new(variant, "expected `unique` or `sorted`")),
+        };
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: array_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VectorOp {
+    Dot,
+    Add,
+    Sub,
+    Mul,
+    Scale,
+}
+
+impl VectorOp {
+    const ALL: [Self; 5] = [Self::Dot, Self::Add, Self::Sub, Self::Mul, Self::Scale];
+}
+
+impl Display for VectorOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Dot => "dot",
+                Self::Add => "add",
+                Self::Sub => "sub",
+                Self::Mul => "mul",
+                Self::Scale => "scale",
+            }
+        )
+    }
+}
+
+impl FromStr for VectorOp {
+    type Err = VectorOpParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(Self::Dot),
+            "add" => Ok(Self::Add),
+            "sub" => Ok(Self::Sub),
+            "mul" => Ok(Self::Mul),
+            "scale" => Ok(Self::Scale),
+            s => Err(VectorOpParseError::InvalidVariant(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum VectorOpParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+/// Numbers of an array, `None` if any element is not a number
+fn array_numbers(array: &Rc<dyn Object>) -> Option<Vec<f64>> {
+    array_items(array)
+        .iter()
+        .map(|item| (item.class() == number_class()).then(|| item.as_number()))
+        .collect()
+}
+
+/// Operations on arrays of numbers as vectors. `dot` outputs the dot product of two arrays, `add`,
+/// `sub` and `mul` combine two arrays element by element and `scale` multiplies every element of
+/// an array by a number. Branches to 1 if the arrays differ in length or have elements that are
+/// not numbers.
+#[derive(Debug, Clone)]
+pub struct VectorOpNode(VectorOp);
+
+impl Node for VectorOpNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let Some(lhs) = array_numbers(&context.get_object(0)?) else {
+            return Ok(1);
+        };
+        if self.0 == VectorOp::Scale {
+            let factor = context.get_number(1)?;
+            let res = lhs
+                .into_iter()
+                .map(|n| Rc::new(n * factor) as Rc<dyn Object>)
+                .collect();
+            context.set_outputs(vec![Rc::new(Array(res)) as Rc<dyn Object>]);
+            return Ok(0);
+        }
+        let Some(rhs) = array_numbers(&context.get_object(1)?) else {
+            return Ok(1);
+        };
+        if lhs.len() != rhs.len() {
+            return Ok(1);
+        }
+        let pairs = lhs.into_iter().zip(rhs);
+        let res = match self.0 {
+            VectorOp::Dot => Rc::new(pairs.map(|(a, b)| a * b).sum::<f64>()) as Rc<dyn Object>,
+            op => {
+                let combine = match op {
+                    VectorOp::Add => |a: f64, b: f64| a + b,
+                    VectorOp::Sub => |a, b| a - b,
+                    _ => |a, b| a * b,
+                };
+                let items = pairs
+                    .map(|(a, b)| Rc::new(combine(a, b)) as Rc<dyn Object>)
+                    .collect();
+                Rc::new(Array(items)) as Rc<dyn Object>
+            }
+        };
+        context.set_outputs(vec![res]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        vector_op_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        VectorOp::ALL.iter().map(|v| v.to_string().into()).collect()
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: array_class(),
+                default: None,
+            },
+            InputSocket {
+                class: if self.0 == VectorOp::Scale {
+                    number_class()
+                } else {
+                    array_class()
+                },
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: if self.0 == VectorOp::Dot {
+                number_class()
+            } else {
+                array_class()
+            },
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlattenDepth {
+    One,
+    Deep,
+}
+
+impl FlattenDepth {
+    const ALL: [Self; 2] = [Self::One, Self::Deep];
+}
+
+impl Display for FlattenDepth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::One => "one",
+                Self::Deep => "deep",
+            }
+        )
+    }
+}
+
+impl FromStr for FlattenDepth {
+    type Err = FlattenDepthParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "one" => Ok(Self::One),
+            "deep" => Ok(Self::Deep),
+            s => Err(FlattenDepthParseError::InvalidVariant(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum FlattenDepthParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+/// Append elements of nested arrays to the output, recursing into them if `deep`
+fn flatten_into(items: Vec<Rc<dyn Object>>, deep: bool, out: &mut Vec<Rc<dyn Object>>) {
+    for item in items {
+        if item.class() != array_class() {
+            out.push(item);
+        } else if deep {
+            flatten_into(array_items(&item), deep, out);
+        } else {
+            out.extend(array_items(&item));
+        }
+    }
+}
+
+/// Replace arrays inside an array with their elements. `one` flattens a single level, `deep`
+/// flattens arrays at any depth. Elements that are not arrays are kept as they are.
+#[derive(Debug, Clone)]
+pub struct FlattenNode(FlattenDepth);
+
+impl Node for FlattenNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let mut res = vec![];
+        flatten_into(
+            array_items(&context.get_object(0)?),
+            self.0 == FlattenDepth::Deep,
+            &mut res,
+        );
+        context.set_outputs(vec![Rc::new(Array(res)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        flatten_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        FlattenDepth::ALL
+            .iter()
+            .map(|v| v.to_string().into())
+            .collect()
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: array_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Splits an array into separate outputs, one per element. Amount of outputs is set by the
+/// variant (`destructure-3`). Missing elements are null, extra elements are dropped.
+#[derive(Debug, Clone)]
+pub struct DestructureNode(usize);
+
+impl Node for DestructureNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let mut items = array_items(&context.get_object(0)?);
+        items.resize_with(self.0, || Rc::new(NullType) as Rc<dyn Object>);
+        context.set_outputs(items);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        destructure_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        format!("destructure-{}", self.0).into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant
+            .strip_prefix("destructure-")
+            .ok_or_else(|| VariantError::new(variant, "expected `destructure-` prefix"))?
+            .parse()
+            .map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }; self.0]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Statistics of an array of numbers as a dict with `sum`, `mean`, `min`, `max` and `count`.
+/// For an empty array mean, min and max are null. Branches to 1 if an element is not a number.
+#[derive(Debug, Clone)]
+pub struct ArrayStatsNode;
+
+impl Node for ArrayStatsNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let items = array_items(&context.get_object(0)?);
+        if items.iter().any(|item| item.class() != number_class()) {
+            return Ok(1);
+        }
+        let numbers: Vec<f64> = items.iter().map(|item| item.as_number()).collect();
+        let count = numbers.len() as f64;
+        let sum = numbers.iter().fold(0.0, |sum, n| sum + n);
+        let aggregate = |value: Option<f64>| match value {
+            Some(value) => Rc::new(value) as Rc<dyn Object>,
+            None => Rc::new(NullType) as Rc<dyn Object>,
+        };
+        let stats: Dict = [
+            ("sum", Rc::new(sum) as Rc<dyn Object>),
+            ("mean", aggregate((count > 0.0).then(|| sum / count))),
+            ("min", aggregate(numbers.iter().copied().reduce(f64::min))),
+            ("max", aggregate(numbers.iter().copied().reduce(f64::max))),
+            ("count", Rc::new(count) as Rc<dyn Object>),
+        ]
+        .into_iter()
+        .map(|(name, value)| (Rc::new(name.to_string()) as Rc<dyn Object>, value))
+        .collect();
+        context.set_outputs(vec![Rc::new(stats) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        array_stats_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["array_stats".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "array_stats".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: dict_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Collect inputs into an array, in order. Unlike the array constructor, each input has its own
+/// class, listed in the variant the same way as end node inputs (`pack["number","string"]`).
+#[derive(Debug, Clone)]
+pub struct PackNode(Vec<InputSocket>);
+
+impl Node for PackNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let items = context.get_inputs()?;
+        context.set_outputs(vec![Rc::new(Array(items)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        pack_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["pack[]".into(), self.current_variant()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        format!("pack{}", ron::to_string(&self.0).unwrap()).into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        let inputs = variant
+            .strip_prefix("pack")
+            .ok_or_else(|| VariantError::new(variant, "expected `pack` prefix"))?;
+        self.0 = ron::from_str(inputs).map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        self.0.clone()
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: array_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Split an array into outputs of the classes listed in the variant (`unpack["number","string"]`),
+/// the reverse of `pack`. Branches to 1 if the array length doesn't match the amount of outputs.
+#[derive(Debug, Clone)]
+pub struct UnpackNode(Vec<OutputSocket>);
+
+impl Node for UnpackNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let items = array_items(&context.get_object(0)?);
+        if items.len() != self.0.len() {
+            return Ok(1);
+        }
+        context.set_outputs(items);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        unpack_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["unpack[]".into(), self.current_variant()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        format!("unpack{}", ron::to_string(&self.0).unwrap()).into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        let outputs = variant
+            .strip_prefix("unpack")
+            .ok_or_else(|| VariantError::new(variant, "expected `unpack` prefix"))?;
+        self.0 = ron::from_str(outputs).map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        self.0.clone()
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Index of the first element for which the predicate subroutine, called with the element,
+/// returns a true value. A subroutine that returns nothing counts as returning false.
+fn find_first(
+    context: &mut ExecutionContext,
+    items: &[Rc<dyn Object>],
+) -> Result<Option<usize>, ExecutionError> {
+    let predicate: Subroutine = context.get_object(1)?.to_string().parse().unwrap();
+    for (i, item) in items.iter().enumerate() {
+        let result = context.run_subroutine(predicate.start().clone(), vec![Rc::clone(item)])?;
+        if result.first().is_some_and(|res| res.as_bool()) {
+            return Ok(Some(i));
+        }
+    }
+    Ok(None)
+}
+
+/// Index of the first element of an array for which the predicate subroutine returns a true
+/// value, -1 if there is none. Elements after it are not checked.
+#[derive(Debug, Clone)]
+pub struct ArrayFindIndexNode;
+
+impl Node for ArrayFindIndexNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let items = array_items(&context.get_object(0)?);
+        let res = find_first(context, &items)?.map_or(-1.0, |i| i as f64);
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        find_index_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["find_index".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "find_index".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: array_class(),
+                default: None,
+            },
+            InputSocket {
+                class: subroutine_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// First element of an array for which the predicate subroutine returns a true value, null if
+/// there is none. Elements after it are not checked.
+#[derive(Debug, Clone)]
+pub struct ArrayFindNode;
+
+impl Node for ArrayFindNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let items = array_items(&context.get_object(0)?);
+        let res = match find_first(context, &items)? {
+            Some(i) => Rc::clone(&items[i]),
+            None => Rc::new(NullType) as Rc<dyn Object>,
+        };
+        context.set_outputs(vec![res]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        find_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["find".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "find".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: array_class(),
+                default: None,
+            },
+            InputSocket {
+                class: subroutine_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{array_items, Array};
+    use crate::test_utils::{run_node, TestProgram};
+    use std::rc::Rc;
+
+    fn range(start: &str, end: &str, step: &str) -> Option<Vec<String>> {
+        run_node(
+            "range",
+            "range",
+            &[("number", start), ("number", end), ("number", step)],
+            1,
+        )
+    }
+
+    #[test]
+    fn ascending_range() {
+        assert_eq!(range("0", "5", "2").unwrap(), ["[0, 2, 4]"]);
+    }
+
+    #[test]
+    fn descending_range() {
+        assert_eq!(range("3", "0", "-1").unwrap(), ["[3, 2, 1]"]);
+        assert_eq!(range("0", "3", "-1").unwrap(), ["[]"]);
+    }
+
+    #[test]
+    fn invalid_ranges_are_errors() {
+        assert_eq!(range("0", "5", "0"), None);
+        assert_eq!(range("0", "inf", "1"), None);
+        assert_eq!(range("0", "NaN", "1"), None);
+        assert_eq!(range("1e16", "1e17", "1"), None);
+    }
+
+    #[test]
+    fn range_feeds_array_nodes() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "range", "range")
+            .const_input(1, 0, "1")
+            .const_input(1, 1, "4")
+            .node(2, "join", "join")
+            .const_input(2, 1, "-")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0);
+        assert_eq!(program.run_strings(), ["1-2-3"]);
+    }
+
+    #[test]
+    fn enumerate_pairs() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "array", r#"["a", "b"]"#)
+            .node(2, "enumerate", "enumerate")
+            .end(9, 2)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0)
+            .connect(1, 0, 9, 1);
+        let outputs = program.run();
+        assert_eq!(outputs[0].to_string(), "[[0, a], [1, b]]");
+        let pairs = outputs[0].downcast_ref::<Array>().unwrap();
+        let items = outputs[1].downcast_ref::<Array>().unwrap();
+        for (pair, item) in pairs.0.iter().zip(&items.0) {
+            let pair = pair.downcast_ref::<Array>().unwrap();
+            assert_eq!(pair.0[0].class().name, "number");
+            assert!(Rc::ptr_eq(&pair.0[1], item));
+        }
+    }
+
+    #[test]
+    fn destructure_pads_with_null() {
+        let outputs = run_node("destructure", "destructure-3", &[("array", "[10, 20]")], 3);
+        assert_eq!(outputs.unwrap(), ["10", "20", "null"]);
+    }
+
+    fn stats(array: &str) -> Option<Vec<String>> {
+        run_node("array_stats", "array_stats", &[("array", array)], 1)
+    }
+
+    #[test]
+    fn stats_of_numbers() {
+        assert_eq!(
+            stats("[4, 1, 7]").unwrap(),
+            ["{count: 3, max: 7, mean: 4, min: 1, sum: 12}"]
+        );
+    }
+
+    #[test]
+    fn stats_of_empty_array() {
+        assert_eq!(
+            stats("[]").unwrap(),
+            ["{count: 0, max: null, mean: null, min: null, sum: 0}"]
+        );
+    }
+
+    #[test]
+    fn stats_of_mixed_array_is_an_error() {
+        assert_eq!(stats(r#"[1, "2"]"#), None);
+    }
+
+    #[test]
+    fn pack_and_unpack_back() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "number", "1")
+            .value(2, "string", "two")
+            .value(3, "bool", "true")
+            .node(4, "pack", r#"pack["number","string","bool"]"#)
+            .node(5, "unpack", r#"unpack["number","string","bool"]"#)
+            .end(9, 4)
+            .chain(&[0, 1, 2, 3, 4, 5, 9])
+            .connect(1, 0, 4, 0)
+            .connect(2, 0, 4, 1)
+            .connect(3, 0, 4, 2)
+            .connect(4, 0, 5, 0)
+            .connect(4, 0, 9, 0);
+        for output in 0..3 {
+            program.connect(5, output, 9, output + 1);
+        }
+        assert_eq!(
+            program.run_strings(),
+            ["[1, two, true]", "1", "two", "true"]
+        );
+    }
+
+    #[test]
+    fn unpack_wrong_length() {
+        let array = [("array", "[1, 2]")];
+        assert_eq!(
+            run_node("unpack", r#"unpack["any","any","any"]"#, &array, 3),
+            None
+        );
+    }
+
+    fn flatten(variant: &str, array: &str) -> String {
+        run_node("flatten", variant, &[("array", array)], 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn flatten_one_level() {
+        assert_eq!(flatten("one", "[[1, 2], [3, [4]]]"), "[1, 2, 3, [4]]");
+        assert_eq!(flatten("one", r#"[1, ["a"], []]"#), "[1, a]");
+    }
+
+    #[test]
+    fn flatten_deep() {
+        assert_eq!(flatten("deep", "[[1, 2], [3, [4]]]"), "[1, 2, 3, 4]");
+        assert_eq!(flatten("deep", "[[[[]]], 5, [[6, [7]]]]"), "[5, 6, 7]");
+    }
+
+    fn max_len(array: &str) -> String {
+        run_node("max_len", "max_len", &[("array", array)], 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn longest_element() {
+        assert_eq!(max_len(r#"["a", "abc", "ab"]"#), "3");
+        assert_eq!(max_len(r#"[[1, 2, 3, 4], "héllo"]"#), "5");
+        assert_eq!(max_len("[]"), "0");
+    }
+
+    /// Random choice from and shuffle of the numbers 1 to 10, with the executor seeded
+    fn choice_and_shuffle(seed: u64) -> (String, String) {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "array", "[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]")
+            .node(2, "choice", "choice")
+            .node(3, "shuffle", "shuffle")
+            .end(9, 2)
+            .chain(&[0, 1, 2, 3, 9])
+            .connect(1, 0, 2, 0)
+            .connect(1, 0, 3, 0)
+            .connect(2, 0, 9, 0)
+            .connect(3, 0, 9, 1);
+        let mut executor = program.executor();
+        executor.set_seed(seed);
+        executor.start_execution(true).unwrap();
+        let output = executor.final_output().unwrap();
+        (output[0].to_string(), output[1].to_string())
+    }
+
+    #[test]
+    fn seeded_choice_and_shuffle_repeat() {
+        let (choice, shuffled) = choice_and_shuffle(3);
+        assert_eq!(choice_and_shuffle(3), (choice.clone(), shuffled.clone()));
+        assert_ne!(choice_and_shuffle(4).1, shuffled);
+
+        let number: f64 = choice.parse().unwrap();
+        assert!((1.0..=10.0).contains(&number));
+        let shuffled: Array = shuffled.parse().unwrap();
+        let mut numbers: Vec<f64> = shuffled.0.iter().map(|item| item.as_number()).collect();
+        numbers.sort_by(f64::total_cmp);
+        assert_eq!(numbers, (1..=10).map(f64::from).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn choice_from_empty_array() {
+        assert_eq!(run_node("choice", "choice", &[("array", "[]")], 1), None);
+        let shuffled = run_node("shuffle", "shuffle", &[("array", "[]")], 1);
+        assert_eq!(shuffled.unwrap(), ["[]"]);
+    }
+
+    fn unique(variant: &str, array: &str) -> String {
+        run_node("unique", variant, &[("array", array)], 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn unique_numbers() {
+        assert_eq!(unique("unique", "[1, 2, 2, 3, 1]"), "[1, 2, 3]");
+        assert_eq!(unique("sorted", "[1, 1, 2, 3, 3, 3]"), "[1, 2, 3]");
+        assert_eq!(unique("sorted", "[1, 2, 1]"), "[1, 2, 1]");
+    }
+
+    #[test]
+    fn unique_by_object_equality() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(
+                1,
+                "array",
+                r#"["a", "b", "a", 1, "1", [1, "a"], [1, "a"], [1]]"#,
+            )
+            .node(2, "unique", "unique")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0);
+        let items: Vec<(String, String)> = array_items(&program.run()[0])
+            .iter()
+            .map(|item| (item.class().name, item.to_string()))
+            .collect();
+        let expected = [
+            ("string", "a"),
+            ("string", "b"),
+            ("number", "1"),
+            ("string", "1"),
+            ("array", "[1, a]"),
+            ("array", "[1]"),
+        ];
+        assert_eq!(items, expected.map(|(c, t)| (c.to_string(), t.to_string())));
+    }
+
+    fn vector_op(variant: &str, lhs: &str, rhs: (&str, &str)) -> Option<Vec<String>> {
+        run_node("vector_op", variant, &[("array", lhs), rhs], 1)
+    }
+
+    #[test]
+    fn dot_product() {
+        let dot = vector_op("dot", "[1, 2, 3]", ("array", "[4, 5, 6]"));
+        assert_eq!(dot.unwrap(), ["32"]);
+    }
+
+    #[test]
+    fn element_wise_operations() {
+        let lhs = "[1, 2, 3]";
+        let rhs = ("array", "[4, 5, 6]");
+        assert_eq!(vector_op("add", lhs, rhs).unwrap(), ["[5, 7, 9]"]);
+        assert_eq!(vector_op("sub", lhs, rhs).unwrap(), ["[-3, -3, -3]"]);
+        assert_eq!(vector_op("mul", lhs, rhs).unwrap(), ["[4, 10, 18]"]);
+        let scaled = vector_op("scale", lhs, ("number", "2"));
+        assert_eq!(scaled.unwrap(), ["[2, 4, 6]"]);
+    }
+
+    #[test]
+    fn vector_op_errors() {
+        assert_eq!(vector_op("add", "[1, 2]", ("array", "[1, 2, 3]")), None);
+        assert_eq!(vector_op("dot", "[1, 2]", ("array", "[1]")), None);
+        assert_eq!(vector_op("add", r#"[1, "2"]"#, ("array", "[1, 2]")), None);
+        assert_eq!(vector_op("scale", "[true]", ("number", "2")), None);
+    }
+
+    /// Run a find node of a class on an array with a predicate subroutine that checks if a number
+    /// is even
+    fn find_even(class: &str, array: &str) -> Vec<String> {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "array", array)
+            .node(2, class, class)
+            .const_input(2, 1, "subroutine:__main__@20:__main__@29")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0);
+        program
+            .node(20, "start", r#"start#sub#["any"]"#)
+            .node(21, "number_theory", "modpow")
+            .connect(20, 0, 21, 0)
+            .const_input(21, 1, "1")
+            .const_input(21, 2, "2")
+            .value(22, "number", "0")
+            .node(23, "equals", "eq")
+            .connect(21, 0, 23, 0)
+            .connect(22, 0, 23, 1)
+            .end(29, 1)
+            .connect(23, 0, 29, 0)
+            .chain(&[20, 21, 22, 23, 29]);
+        program.run_strings()
+    }
+
+    #[test]
+    fn find_first_even() {
+        assert_eq!(find_even("find_index", "[1, 3, 4, 5]"), ["2"]);
+        assert_eq!(find_even("find", "[1, 3, 4, 5, 6]"), ["4"]);
+    }
+
+    #[test]
+    fn find_without_match() {
+        assert_eq!(find_even("find_index", "[1, 3, 5]"), ["-1"]);
+        assert_eq!(find_even("find", "[1, 3, 5]"), ["null"]);
+        assert_eq!(find_even("find_index", "[]"), ["-1"]);
+    }
+}
@@ -3,18 +3,26 @@ use crate::{
     node::Node,
     object::{Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd},
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
 
-use super::any_class;
+use super::{any_class, cached_class};
 use std::{borrow::Cow, rc::Rc};
 
 pub fn bool_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "bool".into(),
         nodes: vec![Rc::new(BoolNode) as Rc<dyn Node>],
         obj_from_str: Some(<bool as ObjectFromStr>::from_str),
-    }
+    })
+}
+
+pub fn to_bool_class() -> Class {
+    cached_class(|| Class {
+        name: "to_bool".into(),
+        nodes: vec![Rc::new(ToBoolNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
 }
 
 #[derive(Debug, Clone)]
@@ -63,10 +71,10 @@ impl ObjectOrd for bool {
 }
 
 impl Node for BoolNode {
-    fn execute(&self, context: &mut ExecutionContext) -> usize {
-        let cond = context.get_inputs()[0].as_bool();
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let cond = context.get_object(0)?.as_bool();
         context.set_outputs(vec![Rc::new(cond) as Rc<dyn Object>]);
-        0
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -84,7 +92,54 @@ impl Node for BoolNode {
     fn set_variant(&mut self, _variant: &str) {}
 
     fn inputs(&self) -> Vec<InputSocket> {
-        vec![InputSocket { class: any_class() }]
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: bool_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Truthiness of an object, the same as used by conditions. Numbers are true if not 0, strings,
+/// arrays, dicts and sets if not empty, null is false and other objects decide themselves.
+#[derive(Debug, Clone)]
+pub struct ToBoolNode;
+
+impl Node for ToBoolNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = context.get_object(0)?.as_bool();
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        to_bool_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["to_bool".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "to_bool".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
     }
 
     fn outputs(&self) -> Vec<OutputSocket> {
@@ -97,3 +152,23 @@ impl Node for BoolNode {
         Rc::new(self.clone()) as Rc<dyn Node>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::run_node;
+
+    fn to_bool(class: &str, text: &str) -> String {
+        run_node("to_bool", "to_bool", &[(class, text)], 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn truthiness() {
+        assert_eq!(to_bool("number", "0"), "false");
+        assert_eq!(to_bool("number", "-2"), "true");
+        assert_eq!(to_bool("string", ""), "false");
+        assert_eq!(to_bool("string", "false"), "true");
+        assert_eq!(to_bool("array", "[]"), "false");
+        assert_eq!(to_bool("array", "[0]"), "true");
+        assert_eq!(to_bool("null", "null"), "false");
+    }
+}
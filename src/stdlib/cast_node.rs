@@ -0,0 +1,130 @@
+use super::{any_class, cached_class};
+use crate::{
+    class::Class,
+    module::ModulePath,
+    node::{Node, VariantError},
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, rc::Rc};
+
+pub fn cast_class() -> Class {
+    cached_class(|| Class {
+        name: "cast".into(),
+        nodes: vec![
+            Rc::new(CastNode(ModulePath(vec!["std".into()], "string".into()))) as Rc<dyn Node>,
+        ],
+        obj_from_str: None,
+    })
+}
+
+/// Explicitly cast an object to a class. Branches to 1 if the class is not loaded or the object
+/// can't be cast to it.
+#[derive(Debug, Clone)]
+pub struct CastNode(ModulePath);
+
+impl Node for CastNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let input = context.get_object(0)?;
+        let Some(class) = context.get_class(&self.0) else {
+            return Ok(1);
+        };
+        let casted = if input.class() == class {
+            Some(input)
+        } else {
+            input.try_cast_to(&class)
+        };
+        match casted {
+            Some(obj) => {
+                context.set_outputs(vec![obj]);
+                Ok(0)
+            }
+            None => Ok(1),
+        }
+    }
+
+    fn class(&self) -> Class {
+        cast_class()
+    }
+
+    /// Format: cast:<class_path>
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: cast:<class_path>
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("cast:{}", self.0).into()
+    }
+
+    /// Format: cast:<class_path>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant
+            .strip_prefix("cast:")
+            .ok_or_else(|| VariantError::new(variant, "expected `cast:` prefix"))?
+            .parse()
+            .map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    /// Only the name of the class is known until the node is executed
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: Class {
+                name: self.0 .1.clone(),
+                nodes: vec![],
+                obj_from_str: None,
+            },
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::run_node;
+
+    fn cast(to: &str, input: (&str, &str)) -> Option<Vec<String>> {
+        run_node("cast", &format!("cast:std.{to}"), &[input], 1)
+    }
+
+    #[test]
+    fn number_to_string() {
+        assert_eq!(cast("string", ("number", "2.5")).unwrap(), ["2.5"]);
+    }
+
+    #[test]
+    fn string_to_array() {
+        assert_eq!(cast("array", ("string", "[1, \"a\"]")).unwrap(), ["[1, a]"]);
+    }
+
+    #[test]
+    fn unsupported_casts_are_errors() {
+        assert_eq!(cast("number", ("string", "abc")), None);
+        assert_eq!(cast("array", ("string", "1, 2")), None);
+        assert_eq!(cast("range", ("number", "1")), None);
+        assert_eq!(cast("missing", ("number", "1")), None);
+    }
+}
@@ -0,0 +1,570 @@
+use super::{
+    any_class, array_class, array_items, bool_class, cached_class, dict_class, dict_entries,
+};
+use crate::{
+    class::Class,
+    node::Node,
+    object::Object,
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, cmp::Ordering, rc::Rc};
+
+pub fn equals_class() -> Class {
+    cached_class(|| Class {
+        name: "equals".into(),
+        nodes: vec![Rc::new(EqualsNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn not_equals_class() -> Class {
+    cached_class(|| Class {
+        name: "not_equals".into(),
+        nodes: vec![Rc::new(NotEqualsNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn deep_equals_class() -> Class {
+    cached_class(|| Class {
+        name: "deep_equals".into(),
+        nodes: vec![Rc::new(DeepEqualsNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn min_class() -> Class {
+    cached_class(|| Class {
+        name: "min".into(),
+        nodes: vec![Rc::new(MinNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn max_class() -> Class {
+    cached_class(|| Class {
+        name: "max".into(),
+        nodes: vec![Rc::new(MaxNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn between_class() -> Class {
+    cached_class(|| Class {
+        name: "between".into(),
+        nodes: vec![Rc::new(BetweenNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Objects of different classes are never equal
+fn objects_equal(lhs: &Rc<dyn Object>, rhs: &Rc<dyn Object>) -> bool {
+    lhs.class() == rhs.class() && lhs.eq(Rc::clone(rhs))
+}
+
+/// Arrays and dicts are equal when their items are, recursively. Other objects are compared with
+/// `objects_equal`.
+fn objects_deep_equal(lhs: &Rc<dyn Object>, rhs: &Rc<dyn Object>) -> bool {
+    let class = lhs.class();
+    if class != rhs.class() {
+        false
+    } else if class == array_class() {
+        items_deep_equal(&array_items(lhs), &array_items(rhs))
+    } else if class == dict_class() {
+        let (lhs_entries, rhs_entries) = (dict_entries(lhs), dict_entries(rhs));
+        lhs_entries.len() == rhs_entries.len()
+            && lhs_entries.iter().zip(&rhs_entries).all(
+                |((lhs_key, lhs_value), (rhs_key, rhs_value))| {
+                    objects_deep_equal(lhs_key, rhs_key) && objects_deep_equal(lhs_value, rhs_value)
+                },
+            )
+    } else {
+        objects_equal(lhs, rhs)
+    }
+}
+
+/// Ordering of two objects of the same class, `None` if they can't be ordered
+fn objects_order(lhs: &Rc<dyn Object>, rhs: &Rc<dyn Object>) -> Option<Ordering> {
+    if lhs.class() == rhs.class() {
+        lhs.partial_cmp(Rc::clone(rhs))
+    } else {
+        None
+    }
+}
+
+fn items_deep_equal(lhs: &[Rc<dyn Object>], rhs: &[Rc<dyn Object>]) -> bool {
+    lhs.len() == rhs.len()
+        && lhs
+            .iter()
+            .zip(rhs)
+            .all(|(lhs, rhs)| objects_deep_equal(lhs, rhs))
+}
+
+#[derive(Debug, Clone)]
+pub struct EqualsNode;
+
+impl Node for EqualsNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = objects_equal(&context.get_object(0)?, &context.get_object(1)?);
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        equals_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["eq".into()]
+    }
+
+    fn variant_descriptions(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        vec![("eq".into(), "Whether two objects are equal".into())]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "eq".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            2
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: bool_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NotEqualsNode;
+
+impl Node for NotEqualsNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = !objects_equal(&context.get_object(0)?, &context.get_object(1)?);
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        not_equals_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["ne".into()]
+    }
+
+    fn variant_descriptions(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        vec![("ne".into(), "Whether two objects are not equal".into())]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "ne".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            2
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: bool_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Compare containers structurally, descending into nested arrays and dicts
+#[derive(Debug, Clone)]
+pub struct DeepEqualsNode;
+
+impl Node for DeepEqualsNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = objects_deep_equal(&context.get_object(0)?, &context.get_object(1)?);
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        deep_equals_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["deep_eq".into()]
+    }
+
+    fn variant_descriptions(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        vec![(
+            "deep_eq".into(),
+            "Whether two objects are equal, comparing nested arrays and dicts item by item".into(),
+        )]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "deep_eq".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            2
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: bool_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Output the lesser of two objects. The first one is output if they are equal. Branches to 1 if
+/// the objects are of different classes or can't be ordered.
+#[derive(Debug, Clone)]
+pub struct MinNode;
+
+impl Node for MinNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (lhs, rhs) = (context.get_object(0)?, context.get_object(1)?);
+        let res = match objects_order(&lhs, &rhs) {
+            Some(Ordering::Greater) => rhs,
+            Some(_) => lhs,
+            None => return Ok(1),
+        };
+        context.set_outputs(vec![res]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        min_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["min".into()]
+    }
+
+    fn variant_descriptions(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        vec![("min".into(), "The lesser of two objects".into())]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "min".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            2
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Output the greater of two objects. The second one is output if they are equal. Branches to 1
+/// if the objects are of different classes or can't be ordered.
+#[derive(Debug, Clone)]
+pub struct MaxNode;
+
+impl Node for MaxNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (lhs, rhs) = (context.get_object(0)?, context.get_object(1)?);
+        let res = match objects_order(&lhs, &rhs) {
+            Some(Ordering::Greater) => lhs,
+            Some(_) => rhs,
+            None => return Ok(1),
+        };
+        context.set_outputs(vec![res]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        max_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["max".into()]
+    }
+
+    fn variant_descriptions(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        vec![("max".into(), "The greater of two objects".into())]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "max".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            2
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Whether a value is within bounds, inclusive: `low <= value <= high`. Branches to 1 if the
+/// value can't be ordered with either bound.
+#[derive(Debug, Clone)]
+pub struct BetweenNode;
+
+impl Node for BetweenNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (value, low, high) = (
+            context.get_object(0)?,
+            context.get_object(1)?,
+            context.get_object(2)?,
+        );
+        let (Some(to_low), Some(to_high)) =
+            (objects_order(&value, &low), objects_order(&value, &high))
+        else {
+            return Ok(1);
+        };
+        let res = to_low != Ordering::Less && to_high != Ordering::Greater;
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        between_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["between".into()]
+    }
+
+    fn variant_descriptions(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        vec![(
+            "between".into(),
+            "Whether a value is between two bounds, inclusive".into(),
+        )]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "between".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            3
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: bool_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::run_node;
+
+    fn compare(class: &str, lhs: (&str, &str), rhs: (&str, &str)) -> Option<Vec<String>> {
+        let variant = if class == "equals" { "eq" } else { "ne" };
+        run_node(class, variant, &[lhs, rhs], 1)
+    }
+
+    #[test]
+    fn equal_numbers() {
+        let (lhs, rhs) = (("number", "2"), ("number", "2.0"));
+        assert_eq!(compare("equals", lhs, rhs).unwrap(), ["true"]);
+        assert_eq!(compare("not_equals", lhs, rhs).unwrap(), ["false"]);
+    }
+
+    #[test]
+    fn unequal_strings() {
+        let (lhs, rhs) = (("string", "abc"), ("string", "abd"));
+        assert_eq!(compare("equals", lhs, rhs).unwrap(), ["false"]);
+        assert_eq!(compare("not_equals", lhs, rhs).unwrap(), ["true"]);
+    }
+
+    #[test]
+    fn number_and_string_are_not_equal() {
+        let (lhs, rhs) = (("number", "1"), ("string", "1"));
+        assert_eq!(compare("equals", lhs, rhs).unwrap(), ["false"]);
+        assert_eq!(compare("not_equals", lhs, rhs).unwrap(), ["true"]);
+    }
+
+    /// Deep-compare two objects of the same class
+    fn deep_equal(class: &str, lhs: &str, rhs: &str) -> bool {
+        let result = run_node("deep_equals", "deep_eq", &[(class, lhs), (class, rhs)], 1);
+        result.unwrap() == ["true"]
+    }
+
+    #[test]
+    fn nested_containers_are_deep_equal() {
+        let value = r#"[{"a": [1, 2], "b": {"c": "d"}}, 3]"#;
+        assert!(deep_equal("array", value, value));
+    }
+
+    #[test]
+    fn differing_leaf_is_found() {
+        let lhs = r#"[{"a": [1, 2], "b": {"c": "d"}}, 3]"#;
+        let rhs = r#"[{"a": [1, 2], "b": {"c": "e"}}, 3]"#;
+        assert!(!deep_equal("array", lhs, rhs));
+        assert!(!deep_equal("array", "[[1, 2]]", "[[1, 3]]"));
+    }
+
+    #[test]
+    fn dict_with_keys_field_is_compared_by_entries() {
+        assert!(!deep_equal(
+            "dict",
+            r#"{"keys": 1, "a": 2}"#,
+            r#"{"keys": 1, "a": 3}"#
+        ));
+        assert!(deep_equal(
+            "dict",
+            r#"{"keys": 1, "a": 2}"#,
+            r#"{"a": 2, "keys": 1}"#
+        ));
+    }
+
+    fn min_max(lhs: (&str, &str), rhs: (&str, &str)) -> Option<(String, String)> {
+        let min = run_node("min", "min", &[lhs, rhs], 1)?;
+        let max = run_node("max", "max", &[lhs, rhs], 1)?;
+        Some((min[0].clone(), max[0].clone()))
+    }
+
+    #[test]
+    fn min_max_numbers() {
+        let (min, max) = min_max(("number", "3"), ("number", "-1.5")).unwrap();
+        assert_eq!((min.as_str(), max.as_str()), ("-1.5", "3"));
+    }
+
+    #[test]
+    fn min_max_strings() {
+        let (min, max) = min_max(("string", "pear"), ("string", "apple")).unwrap();
+        assert_eq!((min.as_str(), max.as_str()), ("apple", "pear"));
+    }
+
+    #[test]
+    fn min_max_arrays() {
+        let (min, max) = min_max(("array", "[1, 2, 3]"), ("array", "[1, 5]")).unwrap();
+        assert_eq!((min.as_str(), max.as_str()), ("[1, 2, 3]", "[1, 5]"));
+        let (min, max) = min_max(("array", "[1, 2]"), ("array", "[1]")).unwrap();
+        assert_eq!((min.as_str(), max.as_str()), ("[1]", "[1, 2]"));
+    }
+
+    #[test]
+    fn min_max_incomparable() {
+        assert_eq!(min_max(("number", "1"), ("string", "1")), None);
+        assert_eq!(min_max(("array", "[1]"), ("array", r#"["a"]"#)), None);
+    }
+
+    fn between(value: (&str, &str), low: (&str, &str), high: (&str, &str)) -> Option<String> {
+        Some(run_node("between", "between", &[value, low, high], 1)?[0].clone())
+    }
+
+    #[test]
+    fn numbers_between_bounds() {
+        let (low, high) = (("number", "1"), ("number", "10"));
+        assert_eq!(between(("number", "5"), low, high).unwrap(), "true");
+        assert_eq!(between(("number", "1"), low, high).unwrap(), "true");
+        assert_eq!(between(("number", "10"), low, high).unwrap(), "true");
+        assert_eq!(between(("number", "0.5"), low, high).unwrap(), "false");
+        assert_eq!(between(("number", "11"), low, high).unwrap(), "false");
+    }
+
+    #[test]
+    fn strings_between_bounds() {
+        let (low, high) = (("string", "b"), ("string", "d"));
+        assert_eq!(between(("string", "cat"), low, high).unwrap(), "true");
+        assert_eq!(between(("string", "apple"), low, high).unwrap(), "false");
+        assert_eq!(between(("string", "dog"), low, high).unwrap(), "false");
+    }
+
+    #[test]
+    fn arrays_between_bounds() {
+        let (low, high) = (("array", "[1, 2]"), ("array", "[2]"));
+        assert_eq!(between(("array", "[1, 5]"), low, high).unwrap(), "true");
+        assert_eq!(between(("array", "[2, 0]"), low, high).unwrap(), "false");
+    }
+
+    #[test]
+    fn incomparable_bounds() {
+        let (low, high) = (("number", "1"), ("number", "10"));
+        assert_eq!(between(("string", "5"), low, high), None);
+        assert_eq!(between(("number", "5"), low, ("string", "10")), None);
+    }
+}
@@ -0,0 +1,272 @@
+use super::{cached_class, string_class, Array, Dict};
+use crate::{
+    class::Class,
+    module::ModulePath,
+    node::{Node, VariantError},
+    object::Object,
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, rc::Rc};
+
+pub fn construct_class() -> Class {
+    cached_class(|| Class {
+        name: "construct".into(),
+        nodes: vec![Rc::new(ConstructNode(ModulePath(
+            vec!["std".into()],
+            "string".into(),
+        ))) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn default_of_class() -> Class {
+    cached_class(|| Class {
+        name: "default_of".into(),
+        nodes: vec![Rc::new(DefaultOfNode(ModulePath(
+            vec!["std".into()],
+            "number".into(),
+        ))) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Zero value of a class: 0, "", false, [] or {} for the standard classes. Objects of other
+/// classes are made by their `obj_from_str` from an empty string, if that succeeds.
+pub fn default_object(class: &Class) -> Option<Rc<dyn Object>> {
+    match class.name.as_str() {
+        "number" => Some(Rc::new(0.0)),
+        "string" => Some(Rc::new(String::new())),
+        "bool" => Some(Rc::new(false)),
+        "array" => Some(Rc::new(Array(vec![]))),
+        "dict" => Some(Rc::new(Dict::from_iter([]))),
+        _ => (class.obj_from_str?)("").ok(),
+    }
+}
+
+/// Construct an object of a class from its RON text representation at runtime, using the
+/// `obj_from_str` of the class. Objects of classes defined in programs are written as the class
+/// name followed by a RON map of fields, `Point {"x": 1, "y": 2}`. Branches to 1 if the class is
+/// not loaded, can't be constructed from text, the text is invalid or describes an object of a
+/// different class.
+#[derive(Debug, Clone)]
+pub struct ConstructNode(ModulePath);
+
+impl Node for ConstructNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let data = context.get_string(0)?;
+        let Some(obj_from_str) = context.get_class(&self.0).and_then(|c| c.obj_from_str) else {
+            return Ok(1);
+        };
+        match obj_from_str(&data) {
+            Ok(obj) if obj.class().name == self.0 .1 => {
+                context.set_outputs(vec![obj]);
+                Ok(0)
+            }
+            _ => Ok(1),
+        }
+    }
+
+    fn class(&self) -> Class {
+        construct_class()
+    }
+
+    /// Format: construct:<class_path>
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: construct:<class_path>
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("construct:{}", self.0).into()
+    }
+
+    /// Format: construct:<class_path>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant
+            .strip_prefix("construct:")
+            .ok_or_else(|| VariantError::new(variant, "expected `construct:` prefix"))?
+            .parse()
+            .map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: string_class(),
+            default: None,
+        }]
+    }
+
+    /// Only the name of the class is known until the node is executed
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: Class {
+                name: self.0 .1.clone(),
+                nodes: vec![],
+                obj_from_str: None,
+            },
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Output the zero value of a class, see `default_object`. Branches to 1 if the class is not
+/// loaded or has no zero value.
+#[derive(Debug, Clone)]
+pub struct DefaultOfNode(ModulePath);
+
+impl Node for DefaultOfNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let Some(value) = context.get_class(&self.0).and_then(|c| default_object(&c)) else {
+            return Ok(1);
+        };
+        context.set_outputs(vec![value]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        default_of_class()
+    }
+
+    /// Format: default_of:<class_path>
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: default_of:<class_path>
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("default_of:{}", self.0).into()
+    }
+
+    /// Format: default_of:<class_path>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant
+            .strip_prefix("default_of:")
+            .ok_or_else(|| VariantError::new(variant, "expected `default_of:` prefix"))?
+            .parse()
+            .map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    /// Only the name of the class is known until the node is executed
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: Class {
+                name: self.0 .1.clone(),
+                nodes: vec![],
+                obj_from_str: None,
+            },
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        class::ProtoClass,
+        test_utils::{run_node, TestProgram},
+    };
+
+    /// Construct an object of class `Point` defined in the program and end with its fields
+    fn construct_point(text: &str) -> Vec<String> {
+        let mut program = TestProgram::new();
+        program.0.classes.push(ProtoClass {
+            name: "Point".into(),
+            nodes: vec![],
+        });
+        program
+            .start(0)
+            .node(1, "construct", "construct:Point")
+            .const_input(1, 0, text)
+            .node(2, "field_path", "field_path:x")
+            .node(3, "field_path", "field_path:y")
+            .end(9, 2)
+            .chain(&[0, 1, 2, 3, 9])
+            .connect(1, 0, 2, 0)
+            .connect(1, 0, 3, 0)
+            .connect(2, 0, 9, 0)
+            .connect(3, 0, 9, 1)
+            .end(10, 0)
+            .edge(1, 1, 10);
+        program.run_strings()
+    }
+
+    #[test]
+    fn construct_program_class() {
+        assert_eq!(construct_point(r#"Point {"x": 1, "y": 2}"#), ["1", "2"]);
+    }
+
+    #[test]
+    fn construct_other_class_is_an_error() {
+        assert!(construct_point(r#"Line {"x": 1, "y": 2}"#).is_empty());
+    }
+
+    #[test]
+    fn construct_std_classes() {
+        let construct = |class: &str, text: &str| {
+            run_node(
+                "construct",
+                &format!("construct:std.{class}"),
+                &[("string", text)],
+                1,
+            )
+        };
+        assert_eq!(construct("array", "[1, 2]").unwrap(), ["[1, 2]"]);
+        assert_eq!(construct("array", "[1, 2"), None);
+        assert_eq!(construct("number", "x"), None);
+    }
+
+    fn default_of(class: &str) -> Option<Vec<String>> {
+        run_node("default_of", &format!("default_of:{class}"), &[], 1)
+    }
+
+    #[test]
+    fn defaults_of_std_classes() {
+        assert_eq!(default_of("std.number").unwrap(), ["0"]);
+        assert_eq!(default_of("std.string").unwrap(), [""]);
+        assert_eq!(default_of("std.array").unwrap(), ["[]"]);
+        assert_eq!(default_of("std.dict").unwrap(), ["{}"]);
+    }
+
+    #[test]
+    fn default_of_unsupported_class_is_an_error() {
+        assert_eq!(default_of("std.subroutine"), None);
+        assert_eq!(default_of("std.missing"), None);
+    }
+}
@@ -1,20 +1,30 @@
-use std::{collections::BTreeMap, fmt::Display, ops::Deref, rc::Rc, str::FromStr};
+use std::{borrow::Cow, collections::BTreeMap, fmt::Display, ops::Deref, rc::Rc, str::FromStr};
 
 use stainless_script_derive::{ObjectEq, ObjectOrd, ObjectPartialEq, ObjectPartialOrd};
 use thiserror::Error;
 
 use crate::{
     class::Class,
-    object::{Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd},
+    module::ModulePath,
+    node::{Node, VariantError},
+    object::{
+        class_hash, Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd,
+    },
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
 };
 
-use super::{AnyType, Array};
+use super::{
+    array_class, array_items, cached_class, string_class, subroutine_class, AnyType, Array,
+    NullType, Subroutine,
+};
 
+/// Wrapper that orders objects, so they can be used as keys of dicts and items of sets
 #[derive(Debug, Clone)]
-struct DictVal(Rc<dyn Object>);
+pub(super) struct DictVal(pub(super) Rc<dyn Object>);
 
 impl DictVal {
-    fn from_ron(val: &ron::Value) -> Self {
+    pub(super) fn from_ron(val: &ron::Value) -> Self {
         match val {
             ron::Value::Bool(b) => Self(Rc::new(*b) as Rc<dyn Object>),
             ron::Value::Char(c) => Self(Rc::new(c.to_string()) as Rc<dyn Object>),
@@ -41,11 +51,31 @@ impl DictVal {
         )
     }
 
-    fn array_from_seq(seq: &[ron::Value]) -> Array {
+    pub(super) fn array_from_seq(seq: &[ron::Value]) -> Array {
         Array(seq.iter().map(|v| DictVal::from_ron(v).0).collect())
     }
 }
 
+/// Text of an object that `DictVal::from_ron` reads back as an equal object. Strings are quoted,
+/// arrays and dicts are written item by item and other objects use their `Display` text.
+pub(super) fn ron_text(object: &(dyn Object + 'static)) -> String {
+    if let Some(string) = object.downcast_ref::<String>() {
+        ron::to_string(string).unwrap()
+    } else if let Some(array) = object.downcast_ref::<Array>() {
+        let items: Vec<String> = array.0.iter().map(|item| ron_text(item.as_ref())).collect();
+        format!("[{}]", items.join(", "))
+    } else if let Some(dict) = object.downcast_ref::<Dict>() {
+        let entries: Vec<String> = dict
+            .0
+            .iter()
+            .map(|(key, value)| format!("{}: {}", ron_text(key.as_ref()), ron_text(value.as_ref())))
+            .collect();
+        format!("{{{}}}", entries.join(", "))
+    } else {
+        object.to_string()
+    }
+}
+
 impl Deref for DictVal {
     type Target = Rc<dyn Object>;
 
@@ -62,24 +92,95 @@ impl PartialEq for DictVal {
 
 impl PartialOrd for DictVal {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.0.partial_cmp(Rc::clone(&other.0))
+        Some(self.cmp(other))
     }
 }
 
 impl Eq for DictVal {}
 
+/// Values of different classes are ordered by class name, so a dict can mix key classes (numbers
+/// go before strings)
 impl Ord for DictVal {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.0.cmp(Rc::clone(&other.0))
+        let (class, other_class) = (self.0.class(), other.0.class());
+        if class == other_class {
+            self.0.cmp(Rc::clone(&other.0))
+        } else {
+            Ord::cmp(&class.name, &other_class.name)
+        }
     }
 }
 
 pub fn dict_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "dict".into(),
         nodes: vec![], // TODO: dict constructor (from pairs of values?)
         obj_from_str: Some(<Dict as ObjectFromStr>::from_str),
-    }
+    })
+}
+
+pub fn dict_to_kv_class() -> Class {
+    cached_class(|| Class {
+        name: "dict_to_kv".into(),
+        nodes: vec![Rc::new(DictToKvNode(KvEncoding::Plain)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn kv_to_dict_class() -> Class {
+    cached_class(|| Class {
+        name: "kv_to_dict".into(),
+        nodes: vec![Rc::new(KvToDictNode(KvEncoding::Plain)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn dict_sorted_keys_class() -> Class {
+    cached_class(|| Class {
+        name: "dict_sorted_keys".into(),
+        nodes: vec![Rc::new(DictSortedKeys) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn dict_sorted_values_class() -> Class {
+    cached_class(|| Class {
+        name: "dict_sorted_values".into(),
+        nodes: vec![Rc::new(DictSortedValues) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn dict_partition_class() -> Class {
+    cached_class(|| Class {
+        name: "dict_partition".into(),
+        nodes: vec![Rc::new(DictPartition) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn dict_entries_class() -> Class {
+    cached_class(|| Class {
+        name: "dict_entries".into(),
+        nodes: vec![Rc::new(DictEntriesNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn from_entries_class() -> Class {
+    cached_class(|| Class {
+        name: "from_entries".into(),
+        nodes: vec![Rc::new(FromEntriesNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn group_by_class() -> Class {
+    cached_class(|| Class {
+        name: "group_by".into(),
+        nodes: vec![Rc::new(GroupByNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
 }
 
 #[derive(
@@ -94,7 +195,7 @@ pub fn dict_class() -> Class {
     ObjectEq,
     ObjectOrd,
 )]
-pub struct Dict(BTreeMap<DictVal, DictVal>);
+pub struct Dict(pub(super) BTreeMap<DictVal, DictVal>);
 
 impl FromStr for Dict {
     type Err = DictParseError;
@@ -110,6 +211,16 @@ impl FromStr for Dict {
     }
 }
 
+impl FromIterator<(Rc<dyn Object>, Rc<dyn Object>)> for Dict {
+    fn from_iter<T: IntoIterator<Item = (Rc<dyn Object>, Rc<dyn Object>)>>(iter: T) -> Self {
+        Self(
+            iter.into_iter()
+                .map(|(k, v)| (DictVal(k), DictVal(v)))
+                .collect(),
+        )
+    }
+}
+
 #[derive(Debug, Clone, Error)]
 pub enum DictParseError {
     //#[error("{0}")]
@@ -164,6 +275,33 @@ impl Object for Dict {
         }
     }
 
+    fn field_names(&self) -> Vec<String> {
+        self.0
+            .keys()
+            .filter(|k| k.class().name == "string")
+            .map(|k| k.as_string())
+            .collect()
+    }
+
+    fn clone_object(&self) -> Rc<dyn Object> {
+        Rc::new(self.clone())
+    }
+
+    /// Strings are quoted, so that the text parses back
+    fn serialize_with_class(&self) -> (ModulePath, String) {
+        (ModulePath(vec![], self.class().name), ron_text(self))
+    }
+
+    /// Hashes of the keys and values are combined, so dicts of equal entries hash the same
+    fn object_hash(&self) -> u64 {
+        let entries = self.0.iter().flat_map(|(key, value)| {
+            [key.object_hash(), value.object_hash()]
+                .into_iter()
+                .flat_map(u64::to_le_bytes)
+        });
+        class_hash("dict", entries)
+    }
+
     fn set_field(&mut self, field: Rc<dyn Object>, value: Rc<dyn Object>) {
         let new_key = DictVal(field);
         let new_val = DictVal(value);
@@ -171,3 +309,700 @@ impl Object for Dict {
         self.0.insert(new_key, new_val);
     }
 }
+
+/// Entries of a dict object in key order. Objects of other classes have no entries.
+pub(crate) fn dict_entries(dict: &Rc<dyn Object>) -> Vec<(Rc<dyn Object>, Rc<dyn Object>)> {
+    dict.downcast_ref::<Dict>()
+        .map(|dict| {
+            dict.0
+                .iter()
+                .map(|(key, value)| (Rc::clone(key), Rc::clone(value)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// How keys and values are written in key-value text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KvEncoding {
+    /// Written as is
+    Plain,
+    /// Percent-encoded, so that they can contain `&`, `=` and other special characters
+    Url,
+}
+
+impl KvEncoding {
+    fn encode(&self, s: &str) -> String {
+        match self {
+            Self::Plain => s.into(),
+            Self::Url => s
+                .bytes()
+                .map(|b| match b {
+                    b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                        (b as char).to_string()
+                    }
+                    b => format!("%{b:02X}"),
+                })
+                .collect(),
+        }
+    }
+
+    /// `None` if the percent-encoding is malformed
+    fn decode(&self, s: &str) -> Option<String> {
+        match self {
+            Self::Plain => Some(s.into()),
+            Self::Url => {
+                let mut bytes = vec![];
+                let mut iter = s.bytes();
+                while let Some(b) = iter.next() {
+                    if b == b'%' {
+                        let hex = [iter.next()?, iter.next()?];
+                        bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+                    } else {
+                        bytes.push(b)
+                    }
+                }
+                String::from_utf8(bytes).ok()
+            }
+        }
+    }
+}
+
+impl Display for KvEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Plain => "plain",
+                Self::Url => "url-encoded",
+            }
+        )
+    }
+}
+
+impl FromStr for KvEncoding {
+    type Err = KvEncodingParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(Self::Plain),
+            "url-encoded" => Ok(Self::Url),
+            s => Err(KvEncodingParseError::InvalidVariant(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum KvEncodingParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+/// Convert a dict to `k1=v1&k2=v2` text, with keys sorted. Keys and values are converted to
+/// strings.
+#[derive(Debug, Clone)]
+pub struct DictToKvNode(KvEncoding);
+
+impl Node for DictToKvNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let mut pairs: Vec<(String, String)> = dict_entries(&context.get_object(0)?)
+            .into_iter()
+            .map(|(key, value)| (key.as_string(), value.as_string()))
+            .collect();
+        pairs.sort();
+        let text = pairs
+            .iter()
+            .map(|(k, v)| format!("{}={}", self.0.encode(k), self.0.encode(v)))
+            .collect::<Vec<String>>()
+            .join("&");
+        context.set_outputs(vec![Rc::new(text) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        dict_to_kv_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["plain".into(), "url-encoded".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: dict_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Parse `k1=v1&k2=v2` text into a dict of strings. A pair without `=` gets an empty value.
+/// Branches to 1 if url-encoding is malformed.
+#[derive(Debug, Clone)]
+pub struct KvToDictNode(KvEncoding);
+
+impl Node for KvToDictNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let text = context.get_string(0)?;
+        let mut dict = BTreeMap::new();
+        for pair in text.split('&').filter(|p| !p.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let (Some(key), Some(value)) = (self.0.decode(key), self.0.decode(value)) else {
+                return Ok(1);
+            };
+            dict.insert(
+                DictVal(Rc::new(key) as Rc<dyn Object>),
+                DictVal(Rc::new(value) as Rc<dyn Object>),
+            );
+        }
+        context.set_outputs(vec![Rc::new(Dict(dict)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        kv_to_dict_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["plain".into(), "url-encoded".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: string_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: dict_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Keys of a dict as an array, in ascending order. Dict entries are always kept sorted by key,
+/// so the order only depends on the keys themselves.
+#[derive(Debug, Clone)]
+pub struct DictSortedKeys;
+
+impl Node for DictSortedKeys {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let dict = context.get_object(0)?;
+        let keys = dict_entries(&dict)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        context.set_outputs(vec![Rc::new(Array(keys)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        dict_sorted_keys_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["dict_sorted_keys".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "dict_sorted_keys".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: dict_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: array_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Values of a dict as an array, in ascending order of their keys
+#[derive(Debug, Clone)]
+pub struct DictSortedValues;
+
+impl Node for DictSortedValues {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let dict = context.get_object(0)?;
+        let values = dict_entries(&dict)
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect();
+        context.set_outputs(vec![Rc::new(Array(values)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        dict_sorted_values_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["dict_sorted_values".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "dict_sorted_values".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: dict_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: array_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Split a dict in two by a predicate subroutine, which is called with the key and value of each
+/// entry. The first output has entries for which the subroutine returned a true value, the second
+/// has the rest. A subroutine that returns nothing counts as returning false.
+#[derive(Debug, Clone)]
+pub struct DictPartition;
+
+impl Node for DictPartition {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let dict = context.get_object(0)?;
+        let predicate: Subroutine = context.get_object(1)?.to_string().parse().unwrap();
+        let (mut matching, mut rest) = (vec![], vec![]);
+        for (key, value) in dict_entries(&dict) {
+            let result = context.run_subroutine(
+                predicate.start().clone(),
+                vec![Rc::clone(&key), Rc::clone(&value)],
+            )?;
+            if result.first().is_some_and(|res| res.as_bool()) {
+                matching.push((key, value))
+            } else {
+                rest.push((key, value))
+            }
+        }
+        context.set_outputs(vec![
+            Rc::new(Dict::from_iter(matching)) as Rc<dyn Object>,
+            Rc::new(Dict::from_iter(rest)) as Rc<dyn Object>,
+        ]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        dict_partition_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["dict_partition".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "dict_partition".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: dict_class(),
+                default: None,
+            },
+            InputSocket {
+                class: subroutine_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![
+            OutputSocket {
+                class: dict_class(),
+            };
+            2
+        ]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Group elements of an array by a key subroutine, which is called with each element. Outputs a
+/// dict from keys to arrays of elements that got that key, in their original order. A subroutine
+/// that returns nothing gives a null key.
+#[derive(Debug, Clone)]
+pub struct GroupByNode;
+
+impl Node for GroupByNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let items = array_items(&context.get_object(0)?);
+        let key_fn: Subroutine = context.get_object(1)?.to_string().parse().unwrap();
+        let mut groups: BTreeMap<DictVal, Vec<Rc<dyn Object>>> = BTreeMap::new();
+        for item in items {
+            let key = context
+                .run_subroutine(key_fn.start().clone(), vec![Rc::clone(&item)])?
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| Rc::new(NullType) as Rc<dyn Object>);
+            groups.entry(DictVal(key)).or_default().push(item);
+        }
+        let res = Dict(
+            groups
+                .into_iter()
+                .map(|(key, items)| (key, DictVal(Rc::new(Array(items)) as Rc<dyn Object>)))
+                .collect(),
+        );
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        group_by_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["group_by".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "group_by".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: array_class(),
+                default: None,
+            },
+            InputSocket {
+                class: subroutine_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: dict_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Entries of a dict as an array of `[key, value]` arrays, in ascending order of keys
+#[derive(Debug, Clone)]
+pub struct DictEntriesNode;
+
+impl Node for DictEntriesNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let entries = dict_entries(&context.get_object(0)?)
+            .into_iter()
+            .map(|(key, value)| Rc::new(Array(vec![key, value])) as Rc<dyn Object>)
+            .collect();
+        context.set_outputs(vec![Rc::new(Array(entries)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        dict_entries_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["dict_entries".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "dict_entries".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: dict_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: array_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Make a dict from an array of `[key, value]` arrays, the reverse of `dict_entries`. Later
+/// entries overwrite earlier ones with the same key. Branches to 1 if an entry is not an array of
+/// two items.
+#[derive(Debug, Clone)]
+pub struct FromEntriesNode;
+
+impl Node for FromEntriesNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let mut dict = BTreeMap::new();
+        for entry in array_items(&context.get_object(0)?) {
+            if entry.class() != array_class() {
+                return Ok(1);
+            }
+            let [key, value]: [Rc<dyn Object>; 2] = match array_items(&entry).try_into() {
+                Ok(pair) => pair,
+                Err(_) => return Ok(1),
+            };
+            dict.insert(DictVal(key), DictVal(value));
+        }
+        context.set_outputs(vec![Rc::new(Dict(dict)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        from_entries_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["from_entries".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "from_entries".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: dict_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{run_node, TestProgram};
+
+    fn to_kv(encoding: &str, dict: &str) -> String {
+        run_node("dict_to_kv", encoding, &[("dict", dict)], 1).unwrap()[0].clone()
+    }
+
+    fn from_kv(encoding: &str, text: &str) -> Option<String> {
+        Some(run_node("kv_to_dict", encoding, &[("string", text)], 1)?[0].clone())
+    }
+
+    #[test]
+    fn kv_round_trip() {
+        let text = to_kv("plain", r#"{"b": 2, "a": "x"}"#);
+        assert_eq!(text, "a=x&b=2");
+        assert_eq!(from_kv("plain", &text).unwrap(), "{a: x, b: 2}");
+    }
+
+    #[test]
+    fn url_encoded_round_trip() {
+        let text = to_kv("url-encoded", r#"{"q": "a&b=c d"}"#);
+        assert_eq!(text, "q=a%26b%3Dc%20d");
+        assert_eq!(from_kv("url-encoded", &text).unwrap(), "{q: a&b=c d}");
+        assert_eq!(from_kv("url-encoded", "q=%2"), None);
+    }
+
+    #[test]
+    fn dict_with_keys_key_to_kv() {
+        assert_eq!(to_kv("plain", r#"{"keys": 1, "a": 2}"#), "a=2&keys=1");
+    }
+
+    #[test]
+    fn sorted_keys_and_values() {
+        let dict = ("dict", r#"{"b": 1, 10: 2, "a": 3, 2: 4, "keys": 5}"#);
+        let keys = run_node("dict_sorted_keys", "dict_sorted_keys", &[dict], 1);
+        assert_eq!(keys.unwrap(), ["[2, 10, a, b, keys]"]);
+        let values = run_node("dict_sorted_values", "dict_sorted_values", &[dict], 1);
+        assert_eq!(values.unwrap(), ["[4, 2, 3, 1, 5]"]);
+    }
+
+    #[test]
+    fn partition_by_value_over_10() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "dict", r#"{"a": 5, "b": 20, "keys": 11, "values": 10}"#)
+            .node(2, "dict_partition", "dict_partition")
+            .const_input(2, 1, "subroutine:__main__@20:__main__@24")
+            .end(9, 2)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0)
+            .connect(2, 1, 9, 1);
+        // Predicate is `-inf <= value <= 10`, so the entries over 10 are the non-matching ones
+        program
+            .node(20, "start", r#"start#sub#["any","any"]"#)
+            .value(21, "number", "-inf")
+            .value(22, "number", "10")
+            .node(23, "between", "between")
+            .end(24, 1)
+            .chain(&[20, 21, 22, 23, 24])
+            .connect(20, 1, 23, 0)
+            .connect(21, 0, 23, 1)
+            .connect(22, 0, 23, 2)
+            .connect(23, 0, 24, 0);
+        assert_eq!(
+            program.run_strings(),
+            ["{a: 5, values: 10}", "{b: 20, keys: 11}"]
+        );
+    }
+
+    /// Group an array by a key subroutine that computes `item mod 2`, optionally packed into an
+    /// array
+    fn group_by_parity(array: &str, packed: bool) -> Vec<String> {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "array", array)
+            .node(2, "group_by", "group_by")
+            .const_input(2, 1, "subroutine:__main__@20:__main__@29")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0);
+        program
+            .node(20, "start", r#"start#sub#["any"]"#)
+            .node(21, "number_theory", "modpow")
+            .connect(20, 0, 21, 0)
+            .const_input(21, 1, "1")
+            .const_input(21, 2, "2")
+            .end(29, 1);
+        if packed {
+            program
+                .node(22, "pack", r#"pack["any"]"#)
+                .connect(21, 0, 22, 0)
+                .connect(22, 0, 29, 0)
+                .chain(&[20, 21, 22, 29]);
+        } else {
+            program.connect(21, 0, 29, 0).chain(&[20, 21, 29]);
+        }
+        program.run_strings()
+    }
+
+    #[test]
+    fn group_by_parity_keys() {
+        let groups = group_by_parity("[1, 2, 3, 4]", false);
+        assert_eq!(groups, ["{0: [2, 4], 1: [1, 3]}"]);
+    }
+
+    #[test]
+    fn group_by_array_keys() {
+        let groups = group_by_parity("[5, 6, 7]", true);
+        assert_eq!(groups, ["{[0]: [6], [1]: [5, 7]}"]);
+    }
+
+    #[test]
+    fn entries_round_trip() {
+        let dict = r#"{"a": [1, 2], "keys": 3}"#;
+        let entries = run_node("dict_entries", "dict_entries", &[("dict", dict)], 1).unwrap();
+        assert_eq!(entries, ["[[a, [1, 2]], [keys, 3]]"]);
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "dict", dict)
+            .node(2, "dict_entries", "dict_entries")
+            .node(3, "from_entries", "from_entries")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 3, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 3, 0)
+            .connect(3, 0, 9, 0);
+        assert_eq!(program.run_strings(), ["{a: [1, 2], keys: 3}"]);
+    }
+
+    #[test]
+    fn malformed_entries() {
+        let from_entries = |array| run_node("from_entries", "from_entries", &[("array", array)], 1);
+        assert_eq!(from_entries(r#"[["a", 1]]"#).unwrap(), ["{a: 1}"]);
+        assert_eq!(from_entries(r#"[["a", 1], ["b"]]"#), None);
+        assert_eq!(from_entries(r#"[["a", 1, 2]]"#), None);
+        assert_eq!(from_entries(r#"["a"]"#), None);
+    }
+}
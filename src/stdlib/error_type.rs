@@ -0,0 +1,207 @@
+use super::{cached_class, string_class, NullType};
+use crate::{
+    class::Class,
+    node::Node,
+    object::{Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd},
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use stainless_script_derive::{ObjectEq, ObjectOrd};
+use std::{borrow::Cow, convert::Infallible, fmt::Display, rc::Rc, str::FromStr};
+
+pub fn error_class() -> Class {
+    cached_class(|| Class {
+        name: "error".into(),
+        nodes: vec![Rc::new(ErrorNode) as Rc<dyn Node>],
+        obj_from_str: Some(<ErrorObject as ObjectFromStr>::from_str),
+    })
+}
+
+/// Describes what went wrong. Produced by nodes on their error branches.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, ObjectEq, ObjectOrd)]
+pub struct ErrorObject {
+    pub kind: String,
+    pub message: String,
+}
+
+impl ErrorObject {
+    pub fn new(kind: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Format: `<kind>: <message>`. Kind defaults to `error` if there is no separator.
+impl FromStr for ErrorObject {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.split_once(": ") {
+            Some((kind, message)) => Self::new(kind, message),
+            None => Self::new("error", s),
+        })
+    }
+}
+
+impl Display for ErrorObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind, self.message)
+    }
+}
+
+impl Object for ErrorObject {
+    fn class(&self) -> Class {
+        error_class()
+    }
+
+    fn as_number(&self) -> f64 {
+        f64::NAN
+    }
+
+    fn as_bool(&self) -> bool {
+        true
+    }
+
+    fn get_field(&self, field: Rc<dyn Object>) -> Rc<dyn Object> {
+        match field.as_string().as_str() {
+            "kind" => Rc::new(self.kind.clone()),
+            "message" => Rc::new(self.message.clone()),
+            _ => Rc::new(NullType),
+        }
+    }
+
+    fn field_names(&self) -> Vec<String> {
+        vec!["kind".into(), "message".into()]
+    }
+}
+
+impl ObjectPartialEq for ErrorObject {
+    fn eq(&self, other: Rc<dyn Object>) -> bool {
+        other.class() == self.class() && self.to_string() == other.as_string()
+    }
+}
+
+impl ObjectPartialOrd for ErrorObject {
+    fn partial_cmp(&self, other: Rc<dyn Object>) -> Option<std::cmp::Ordering> {
+        if other.class() == self.class() {
+            PartialOrd::partial_cmp(&self.to_string(), &other.as_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Construct an error from kind and message
+#[derive(Debug, Clone)]
+pub struct ErrorNode;
+
+impl Node for ErrorNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let error = ErrorObject::new(context.get_string(0)?, context.get_string(1)?);
+        context.set_outputs(vec![Rc::new(error) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        error_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["new".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "new".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: Some("error".into()),
+            },
+            InputSocket {
+                class: string_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: error_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ErrorObject;
+    use crate::{
+        object::Object,
+        test_utils::{run_node, TestProgram},
+    };
+    use std::rc::Rc;
+
+    #[test]
+    fn error_is_not_a_number() {
+        let error = ErrorObject::new("io", "disk full");
+        assert!(error.as_number().is_nan());
+        let field = error.get_field(Rc::new("code".to_string()) as Rc<dyn Object>);
+        assert_eq!(field.class().name, "null");
+    }
+
+    #[test]
+    fn construct_error() {
+        let error = run_node(
+            "error",
+            "new",
+            &[("string", "io"), ("string", "disk full")],
+            1,
+        );
+        assert_eq!(error.unwrap(), ["io: disk full"]);
+    }
+
+    #[test]
+    fn read_error_fields() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "error", "io: disk full")
+            .node(2, "field_path", "field_path:kind")
+            .node(3, "field_path", "field_path:message")
+            .end(9, 2)
+            .chain(&[0, 1, 2, 3, 9])
+            .connect(1, 0, 2, 0)
+            .connect(1, 0, 3, 0)
+            .connect(2, 0, 9, 0)
+            .connect(3, 0, 9, 1);
+        assert_eq!(program.run_strings(), ["io", "disk full"]);
+    }
+
+    #[test]
+    fn error_reaches_catch_branch() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "string", "abc")
+            .node(2, "to_number", "to_number")
+            .node(3, "field_path", "field_path:kind")
+            .end(9, 1)
+            .chain(&[0, 1, 2])
+            .edge(2, 1, 3)
+            .edge(3, 0, 9)
+            .connect(1, 0, 2, 0)
+            .connect(2, 1, 3, 0)
+            .connect(3, 0, 9, 0);
+        assert_eq!(program.run_strings(), ["parse"]);
+    }
+}
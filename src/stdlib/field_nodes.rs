@@ -0,0 +1,401 @@
+use super::{
+    any_class, array_items, bool_class, cached_class, dict_entries, number_class, string_class,
+    Array, Dict, Instance,
+};
+use crate::{
+    class::Class,
+    node::{Node, VariantError},
+    object::Object,
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, rc::Rc};
+
+pub fn spread_class() -> Class {
+    cached_class(|| Class {
+        name: "spread".into(),
+        nodes: vec![Rc::new(SpreadNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn len_class() -> Class {
+    cached_class(|| Class {
+        name: "len".into(),
+        nodes: vec![Rc::new(LenNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn field_path_class() -> Class {
+    cached_class(|| Class {
+        name: "field_path".into(),
+        nodes: vec![Rc::new(FieldPathNode(vec![])) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn contains_class() -> Class {
+    cached_class(|| Class {
+        name: "contains".into(),
+        nodes: vec![Rc::new(ContainsNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Size of a container: element count of an array, entry count of a dict or char count of a
+/// string. Branches to 1 for other classes.
+#[derive(Debug, Clone)]
+pub struct LenNode;
+
+impl Node for LenNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let input = context.get_object(0)?;
+        let len = if let Some(array) = input.downcast_ref::<Array>() {
+            array.0.len()
+        } else if let Some(dict) = input.downcast_ref::<Dict>() {
+            dict.0.len()
+        } else if input.class() == string_class() {
+            input.as_string().chars().count()
+        } else {
+            return Ok(1);
+        };
+        context.set_outputs(vec![Rc::new(len as f64) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        len_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["len".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "len".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Copy all fields of the source object onto a copy of the target object. Branches to 1 if the
+/// target is not a dict or an instance of a class, the only objects fields can be added to.
+#[derive(Debug, Clone)]
+pub struct SpreadNode;
+
+impl Node for SpreadNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (source, target) = (context.get_object(0)?, context.get_object(1)?);
+        if target.downcast_ref::<Dict>().is_none() && target.downcast_ref::<Instance>().is_none() {
+            return Ok(1);
+        }
+        let mut result = target.clone_object();
+        let result_mut = Rc::get_mut(&mut result).unwrap();
+        for name in source.field_names() {
+            let field = Rc::new(name) as Rc<dyn Object>;
+            result_mut.set_field(Rc::clone(&field), source.get_field(field));
+        }
+        context.set_outputs(vec![result]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        spread_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["spread".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "spread".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None,
+            };
+            2
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Whether a container has the second input: an element of an array, a key of a dict or a
+/// substring of a string. Branches to 1 for other classes.
+#[derive(Debug, Clone)]
+pub struct ContainsNode;
+
+impl Node for ContainsNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (container, needle) = (context.get_object(0)?, context.get_object(1)?);
+        let is_in = |items: Vec<Rc<dyn Object>>| {
+            items
+                .into_iter()
+                .any(|item| item.class() == needle.class() && item.eq(Rc::clone(&needle)))
+        };
+        let res = match container.class().name.as_str() {
+            "array" => is_in(array_items(&container)),
+            "dict" => is_in(
+                dict_entries(&container)
+                    .into_iter()
+                    .map(|(key, _)| key)
+                    .collect(),
+            ),
+            "string" => container.as_string().contains(&needle.as_string()),
+            _ => return Ok(1),
+        };
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        contains_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["contains".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "contains".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None,
+            };
+            2
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: bool_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Get a nested field by its dotted path (`field_path:user.address.city`). Branches to 1 if any
+/// field along the path is missing.
+#[derive(Debug, Clone)]
+pub struct FieldPathNode(Vec<String>);
+
+impl Node for FieldPathNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let mut value = context.get_object(0)?;
+        for name in &self.0 {
+            if !value.field_names().contains(name) {
+                return Ok(1);
+            }
+            value = value.get_field(Rc::new(name.clone()) as Rc<dyn Object>);
+        }
+        context.set_outputs(vec![value]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        field_path_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("field_path:{}", self.0.join(".")).into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        let path = variant
+            .strip_prefix("field_path:")
+            .ok_or_else(|| VariantError::new(variant, "expected `field_path:` prefix"))?;
+        self.0 = if path.is_empty() {
+            vec![]
+        } else {
+            path.split('.').map(String::from).collect()
+        };
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::run_node;
+
+    fn len(input: (&str, &str)) -> Option<Vec<String>> {
+        run_node("len", "len", &[input], 1)
+    }
+
+    #[test]
+    fn spread_dict_onto_dict() {
+        let spread = run_node(
+            "spread",
+            "spread",
+            &[
+                ("dict", r#"{"a": 1, "b": 2}"#),
+                ("dict", r#"{"b": 3, "c": 4}"#),
+            ],
+            1,
+        );
+        assert_eq!(spread.unwrap(), ["{a: 1, b: 2, c: 4}"]);
+    }
+
+    #[test]
+    fn spread_onto_number_is_an_error() {
+        let spread = run_node(
+            "spread",
+            "spread",
+            &[("dict", r#"{"a": 1}"#), ("number", "1")],
+            1,
+        );
+        assert_eq!(spread, None);
+    }
+
+    #[test]
+    fn container_lengths() {
+        assert_eq!(len(("array", "[1, 2, 3]")).unwrap(), ["3"]);
+        assert_eq!(len(("dict", r#"{"a": 1, "b": 2}"#)).unwrap(), ["2"]);
+        assert_eq!(len(("string", "héllo")).unwrap(), ["5"]);
+    }
+
+    #[test]
+    fn dict_key_named_len_is_counted() {
+        assert_eq!(len(("dict", r#"{"len": 5}"#)).unwrap(), ["1"]);
+    }
+
+    #[test]
+    fn number_has_no_length() {
+        assert_eq!(len(("number", "3")), None);
+    }
+
+    fn contains(container: (&str, &str), needle: (&str, &str)) -> Option<Vec<String>> {
+        run_node("contains", "contains", &[container, needle], 1)
+    }
+
+    #[test]
+    fn array_contains_element() {
+        let array = ("array", r#"[1, "a", [2, 3]]"#);
+        assert_eq!(contains(array, ("number", "1")).unwrap(), ["true"]);
+        assert_eq!(contains(array, ("string", "a")).unwrap(), ["true"]);
+        assert_eq!(contains(array, ("array", "[2, 3]")).unwrap(), ["true"]);
+        assert_eq!(contains(array, ("array", "[2]")).unwrap(), ["false"]);
+        assert_eq!(contains(array, ("string", "1")).unwrap(), ["false"]);
+    }
+
+    #[test]
+    fn dict_contains_key() {
+        let dict = ("dict", r#"{"a": 1, "keys": 2}"#);
+        assert_eq!(contains(dict, ("string", "a")).unwrap(), ["true"]);
+        assert_eq!(contains(dict, ("string", "keys")).unwrap(), ["true"]);
+        assert_eq!(contains(dict, ("number", "1")).unwrap(), ["false"]);
+    }
+
+    #[test]
+    fn string_contains_substring() {
+        let string = ("string", "stainless");
+        assert_eq!(contains(string, ("string", "less")).unwrap(), ["true"]);
+        assert_eq!(contains(string, ("string", "more")).unwrap(), ["false"]);
+    }
+
+    #[test]
+    fn number_is_not_a_container() {
+        assert_eq!(contains(("number", "12"), ("number", "1")), None);
+    }
+
+    fn field_path(path: &str) -> Option<Vec<String>> {
+        let user = r#"{"user": {"name": "Ann", "address": {"city": "Oslo"}}}"#;
+        run_node("field_path", path, &[("dict", user)], 1)
+    }
+
+    #[test]
+    fn nested_field() {
+        assert_eq!(
+            field_path("field_path:user.address.city").unwrap(),
+            ["Oslo"]
+        );
+        assert_eq!(field_path("field_path:user.name").unwrap(), ["Ann"]);
+    }
+
+    #[test]
+    fn missing_intermediate_field_is_an_error() {
+        assert_eq!(field_path("field_path:user.location.city"), None);
+        assert_eq!(field_path("field_path:user.name.first"), None);
+    }
+}
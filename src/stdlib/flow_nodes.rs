@@ -1,28 +1,45 @@
+use super::{any_class, bool_class, cached_class};
 use crate::{
     class::Class,
-    node::Node,
+    node::{Node, VariantError},
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
 use std::rc::Rc;
 
 pub fn start_node_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "start".into(),
         nodes: vec![Rc::new(StartNode {
             outputs: vec![],
             name: "default".into(),
         }) as Rc<dyn Node>],
         obj_from_str: None,
-    }
+    })
 }
 
 pub fn end_node_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "end".into(),
         nodes: vec![Rc::new(EndNode(vec![])) as Rc<dyn Node>],
         obj_from_str: None,
-    }
+    })
+}
+
+pub fn guard_class() -> Class {
+    cached_class(|| Class {
+        name: "guard".into(),
+        nodes: vec![Rc::new(GuardNode(vec![])) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn return_class() -> Class {
+    cached_class(|| Class {
+        name: "return".into(),
+        nodes: vec![Rc::new(ReturnNode(1)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
 }
 
 /// Start of a program or subroutine
@@ -33,8 +50,8 @@ pub struct StartNode {
 }
 
 impl Node for StartNode {
-    fn execute(&self, _context: &mut ExecutionContext) -> usize {
-        0
+    fn execute(&self, _context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -55,12 +72,21 @@ impl Node for StartNode {
     }
 
     fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
         let mut parts = variant.split('#');
         parts.next();
-        let name = String::from(parts.next().unwrap());
-        let outputs = ron::from_str(parts.next().unwrap()).unwrap();
-        self.name = name;
-        self.outputs = outputs
+        let (Some(name), Some(outputs)) = (parts.next(), parts.next()) else {
+            return Err(VariantError::new(
+                variant,
+                "expected `start#<name>#<outputs>`",
+            ));
+        };
+        self.outputs = ron::from_str(outputs).map_err(|e| VariantError::new(variant, e))?;
+        self.name = name.into();
+        Ok(())
     }
 
     fn inputs(&self) -> Vec<InputSocket> {
@@ -85,10 +111,10 @@ impl Node for StartNode {
 pub struct EndNode(Vec<InputSocket>);
 
 impl Node for EndNode {
-    fn execute(&self, context: &mut ExecutionContext) -> usize {
-        let inputs = context.get_inputs();
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let inputs = context.get_inputs()?;
         context.finish_subroutine(inputs);
-        0
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -104,7 +130,15 @@ impl Node for EndNode {
     }
 
     fn set_variant(&mut self, variant: &str) {
-        self.0 = ron::from_str(variant.strip_prefix("end").unwrap()).unwrap()
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        let inputs = variant
+            .strip_prefix("end")
+            .ok_or_else(|| VariantError::new(variant, "expected `end` prefix"))?;
+        self.0 = ron::from_str(inputs).map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
     }
 
     fn inputs(&self) -> Vec<InputSocket> {
@@ -123,3 +157,218 @@ impl Node for EndNode {
         true
     }
 }
+
+/// Return early from a subroutine. If the condition is true, execution continues through branch
+/// 0, otherwise the subroutine finishes like at an end node, returning the rest of the inputs.
+/// Sockets for returned values are listed in the variant the same way as for end nodes.
+#[derive(Debug, Clone)]
+pub struct GuardNode(Vec<InputSocket>);
+
+impl Node for GuardNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        if !context.get_bool(0)? {
+            let returned = context.get_inputs()?.split_off(1);
+            context.finish_subroutine(returned);
+        }
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        guard_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["guard[]".into(), self.current_variant()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        format!("guard{}", ron::to_string(&self.0).unwrap()).into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        let inputs = variant
+            .strip_prefix("guard")
+            .ok_or_else(|| VariantError::new(variant, "expected `guard` prefix"))?;
+        self.0 = ron::from_str(inputs).map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        let mut inputs = vec![InputSocket {
+            class: bool_class(),
+            default: None,
+        }];
+        inputs.extend(self.0.iter().cloned());
+        inputs
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+}
+
+/// Finish a subroutine returning a fixed amount of values, set by the variant. Unlike end node,
+/// fails with `ExecutionError::MissingInput` if any of the values is not supplied.
+#[derive(Debug, Clone)]
+pub struct ReturnNode(usize);
+
+impl Node for ReturnNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let values = context.get_inputs()?;
+        context.finish_subroutine(values);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        return_class()
+    }
+
+    /// Format: return-<amount>
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: return-<amount>
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        format!("return-{}", self.0).into()
+    }
+
+    /// Format: return-<amount>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant
+            .strip_prefix("return-")
+            .ok_or_else(|| VariantError::new(variant, "expected `return-` prefix"))?
+            .parse()
+            .map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            self.0
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        node::{AbsoluteNodeId, NodeId},
+        stdlib::string_class,
+        test_utils::{main_path, TestProgram},
+        ExecutionError,
+    };
+
+    /// Call a subroutine with a guard, returning what the call outputs and whether nodes after
+    /// the guard ran
+    fn guarded_call(condition: &str) -> (String, bool) {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "bool", condition)
+            .node(2, "subroutine", "subroutine:__main__@20:__main__@25")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0);
+        program
+            .node(20, "start", r#"start#sub#["bool"]"#)
+            .value(21, "string", "early")
+            .node(22, "guard", r#"guard["any"]"#)
+            .connect(20, 0, 22, 0)
+            .connect(21, 0, 22, 1)
+            .node(23, "accumulate", "add")
+            .const_input(23, 0, "after_guard")
+            .value(24, "string", "late")
+            .end(25, 1)
+            .connect(24, 0, 25, 0)
+            .chain(&[20, 21, 22, 23, 24, 25]);
+        let mut executor = program.executor();
+        executor.start_execution(true).unwrap();
+        let output = executor.final_output().unwrap()[0].to_string();
+        (output, executor.get_variable("after_guard").is_some())
+    }
+
+    #[test]
+    fn false_guard_returns_early() {
+        assert_eq!(guarded_call("false"), ("early".into(), false));
+    }
+
+    #[test]
+    fn true_guard_passes_through() {
+        assert_eq!(guarded_call("true"), ("late".into(), true));
+    }
+
+    /// Main program reading two values returned from a subroutine with a return node, of which
+    /// only the given amount is supplied
+    fn returning_two(supplied: usize) -> TestProgram {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "subroutine", "subroutine:__main__@20:__main__@23")
+            .end(9, 2)
+            .chain(&[0, 1, 9])
+            .connect(1, 0, 9, 0)
+            .connect(1, 1, 9, 1);
+        program
+            .node(20, "start", "start#sub#[]")
+            .value(21, "number", "1.5")
+            .value(22, "string", "two")
+            .node(23, "return", "return-2")
+            .chain(&[20, 21, 22, 23]);
+        for idx in 0..supplied {
+            program.connect(21 + idx as NodeId, 0, 23, idx);
+        }
+        program
+    }
+
+    #[test]
+    fn return_two_values() {
+        let outputs = returning_two(2).run();
+        assert_eq!(outputs[0].as_number(), 1.5);
+        assert_eq!(outputs[1].class(), string_class());
+        assert_eq!(outputs[1].to_string(), "two");
+    }
+
+    #[test]
+    fn return_missing_value() {
+        let mut executor = returning_two(1).executor();
+        assert!(matches!(
+            executor.start_execution(true),
+            Err(ExecutionError::MissingInput { node, expected: 2, got: 1 })
+                if node == AbsoluteNodeId(main_path(), 23)
+        ));
+    }
+}
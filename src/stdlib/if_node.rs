@@ -1,27 +1,27 @@
-use super::bool_class;
+use super::{bool_class, cached_class};
 use crate::{
     class::Class,
     node::Node,
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
 use std::{borrow::Cow, rc::Rc};
 
 pub fn if_node_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "if".into(),
         nodes: vec![Rc::new(IfNode) as Rc<dyn Node>],
         obj_from_str: None,
-    }
+    })
 }
 
 #[derive(Debug, Clone)]
 pub struct IfNode;
 
 impl Node for IfNode {
-    fn execute(&self, context: &mut ExecutionContext) -> usize {
-        let cond = context.get_inputs()[0].as_bool();
-        cond as usize
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let cond = context.get_bool(0)?;
+        Ok(cond as usize)
     }
 
     fn class(&self) -> Class {
@@ -41,6 +41,7 @@ impl Node for IfNode {
     fn inputs(&self) -> Vec<InputSocket> {
         vec![InputSocket {
             class: bool_class(),
+            default: None,
         }]
     }
 
@@ -0,0 +1,379 @@
+use super::{any_class, cached_class, ron_text, string_class, Dict, DictParseError, Subroutine};
+use crate::{
+    class::Class,
+    module::{ModulePath, ModulePathParseError},
+    node::{AbsoluteNodeId, Node, VariantError},
+    object::{Object, ObjectEq, ObjectOrd, ObjectPartialEq, ObjectPartialOrd},
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, fmt::Display, rc::Rc, str::FromStr};
+use thiserror::Error;
+
+pub fn new_instance_class() -> Class {
+    cached_class(|| Class {
+        name: "new".into(),
+        nodes: vec![Rc::new(NewInstanceNode(ModulePath(vec![], String::new()))) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn method_call_class() -> Class {
+    cached_class(|| Class {
+        name: "method_call".into(),
+        nodes: vec![Rc::new(MethodCallNode {
+            arguments: 0,
+            outputs: 1,
+        }) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Object of a class defined in a program. Fields are stored in a dict.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    /// Where the class is loaded
+    path: ModulePath,
+    class: Class,
+    fields: Dict,
+}
+
+impl Instance {
+    pub fn new(path: ModulePath, class: Class) -> Self {
+        Self {
+            path,
+            class,
+            fields: Dict::from_iter([]),
+        }
+    }
+}
+
+/// Format: `<class_path>` or `<class_path> <fields>`, where fields are a RON map
+impl FromStr for Instance {
+    type Err = InstanceParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (path, fields) = s.trim().split_once(' ').unwrap_or((s.trim(), ""));
+        let path: ModulePath = path.parse()?;
+        let class = Class {
+            name: path.1.clone(),
+            nodes: vec![],
+            obj_from_str: None,
+        };
+        Ok(Self {
+            path,
+            class,
+            fields: if fields.trim().is_empty() {
+                Dict::from_iter([])
+            } else {
+                fields.parse()?
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum InstanceParseError {
+    #[error("Invalid class path: {0}")]
+    ClassPath(#[from] ModulePathParseError),
+    #[error("Invalid fields: {0}")]
+    Fields(#[from] DictParseError),
+}
+
+impl Display for Instance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.class.name, self.fields)
+    }
+}
+
+impl Object for Instance {
+    fn class(&self) -> Class {
+        self.class.clone()
+    }
+
+    fn as_number(&self) -> f64 {
+        panic!("Cannot convert {} to number", self.class.name)
+    }
+
+    fn as_bool(&self) -> bool {
+        true
+    }
+
+    fn get_field(&self, field: Rc<dyn Object>) -> Rc<dyn Object> {
+        self.fields.get_field(field)
+    }
+
+    fn set_field(&mut self, field: Rc<dyn Object>, value: Rc<dyn Object>) {
+        self.fields.set_field(field, value)
+    }
+
+    fn field_names(&self) -> Vec<String> {
+        self.fields.field_names()
+    }
+
+    fn clone_object(&self) -> Rc<dyn Object> {
+        Rc::new(self.clone())
+    }
+
+    fn serialize_with_class(&self) -> (ModulePath, String) {
+        let text = format!("{} {}", self.path, ron_text(&self.fields));
+        (self.path.clone(), text)
+    }
+}
+
+impl ObjectPartialEq for Instance {
+    fn eq(&self, other: Rc<dyn Object>) -> bool {
+        self.class == other.class() && self.to_string() == other.as_string()
+    }
+}
+
+impl ObjectPartialOrd for Instance {
+    fn partial_cmp(&self, other: Rc<dyn Object>) -> Option<std::cmp::Ordering> {
+        if self.class == other.class() {
+            PartialOrd::partial_cmp(&self.to_string(), &other.as_string())
+        } else {
+            None
+        }
+    }
+}
+
+impl ObjectEq for Instance {}
+
+impl ObjectOrd for Instance {
+    fn cmp(&self, other: Rc<dyn Object>) -> std::cmp::Ordering {
+        ObjectPartialOrd::partial_cmp(self, other).unwrap()
+    }
+}
+
+/// Start node of the method of a program-defined class with a name. Methods are the subroutines
+/// called by the nodes of the class, named by their start nodes.
+fn find_method(context: &ExecutionContext, class: &Class, method: &str) -> Option<AbsoluteNodeId> {
+    class.nodes.iter().find_map(|node| {
+        let start = node
+            .current_variant()
+            .parse::<Subroutine>()
+            .ok()?
+            .start()
+            .clone();
+        let variant = context.get_node(&start)?.current_variant().into_owned();
+        (variant.split('#').nth(1)? == method).then_some(start)
+    })
+}
+
+/// Make an object of a class defined in a program, with no fields set. Branches to 1 if the
+/// class is not loaded.
+#[derive(Debug, Clone)]
+pub struct NewInstanceNode(ModulePath);
+
+impl Node for NewInstanceNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let Some(class) = context.get_class(&self.0) else {
+            return Ok(1);
+        };
+        let instance = Instance::new(self.0.clone(), class);
+        context.set_outputs(vec![Rc::new(instance) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        new_instance_class()
+    }
+
+    /// Format: new:<class_path>
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: new:<class_path>
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("new:{}", self.0).into()
+    }
+
+    /// Format: new:<class_path>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant
+            .strip_prefix("new:")
+            .ok_or_else(|| VariantError::new(variant, "expected `new:` prefix"))?
+            .parse()
+            .map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    /// Only the name of the class is known until the node is executed
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: Class {
+                name: self.0 .1.clone(),
+                nodes: vec![],
+                obj_from_str: None,
+            },
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Call a method of an object of a program-defined class by name. The method's subroutine gets
+/// the object as its first input followed by the arguments, and whatever it returns is output.
+/// The amount of arguments and outputs is part of the variant, since the method is only looked up
+/// when the node is executed. Branches to 1 if the class of the object has no such method.
+#[derive(Debug, Clone)]
+pub struct MethodCallNode {
+    arguments: usize,
+    outputs: usize,
+}
+
+impl Node for MethodCallNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let mut inputs = context.get_inputs()?;
+        let method = inputs.remove(1).as_string();
+        let Some(start) = context
+            .find_class(&inputs[0].class().name)
+            .and_then(|class| find_method(context, &class, &method))
+        else {
+            return Ok(1);
+        };
+        let outputs = context.run_subroutine(start, inputs)?;
+        context.set_outputs(outputs);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        method_call_class()
+    }
+
+    /// Format: method_call:<arguments>:<outputs>
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: method_call:<arguments>:<outputs>
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("method_call:{}:{}", self.arguments, self.outputs).into()
+    }
+
+    /// Format: method_call:<arguments>:<outputs>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        let (arguments, outputs) = variant
+            .strip_prefix("method_call:")
+            .and_then(|counts| counts.split_once(':'))
+            .ok_or_else(|| {
+                VariantError::new(variant, "expected `method_call:<arguments>:<outputs>`")
+            })?;
+        self.arguments = arguments
+            .parse()
+            .map_err(|e| VariantError::new(variant, e))?;
+        self.outputs = outputs.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        [
+            InputSocket {
+                class: any_class(),
+                default: None,
+            },
+            InputSocket {
+                class: string_class(),
+                default: None,
+            },
+        ]
+        .into_iter()
+        .chain(vec![
+            InputSocket {
+                class: any_class(),
+                default: None,
+            };
+            self.arguments
+        ])
+        .collect()
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }; self.outputs]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{class::ProtoClass, test_utils::TestProgram};
+
+    /// Call a method of a new `Greeter` object with one argument. The class has one method,
+    /// `echo`, which returns the object and the argument.
+    fn call_method(method: &str) -> Vec<String> {
+        let mut program = TestProgram::new();
+        program.0.classes.push(ProtoClass {
+            name: "Greeter".into(),
+            nodes: vec![50],
+        });
+        program
+            .start(0)
+            .node(1, "new", "new:Greeter")
+            .value(2, "string", "hi")
+            .node(3, "method_call", "method_call:1:2")
+            .const_input(3, 1, method)
+            .connect(1, 0, 3, 0)
+            .connect(2, 0, 3, 2)
+            .end(9, 2)
+            .chain(&[0, 1, 2, 3, 9])
+            .connect(3, 0, 9, 0)
+            .connect(3, 1, 9, 1)
+            .value(8, "string", "no method")
+            .end(10, 1)
+            .edge(3, 1, 8)
+            .edge(8, 0, 10)
+            .connect(8, 0, 10, 0);
+        program
+            .node(50, "subroutine", "subroutine:__main__@20:__main__@21")
+            .node(20, "start", r#"start#echo#["any","any"]"#)
+            .end(21, 2)
+            .chain(&[20, 21])
+            .connect(20, 0, 21, 0)
+            .connect(20, 1, 21, 1);
+        program.run_strings()
+    }
+
+    #[test]
+    fn call_method_on_instance() {
+        assert_eq!(call_method("echo"), ["Greeter {}", "hi"]);
+    }
+
+    #[test]
+    fn call_missing_method() {
+        assert_eq!(call_method("wave"), ["no method"]);
+    }
+}
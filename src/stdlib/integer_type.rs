@@ -0,0 +1,559 @@
+use super::{cached_class, error_class, number_class, string_class, ErrorObject, NullType};
+use crate::{
+    class::Class,
+    node::{Node, VariantError},
+    object::Object,
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, fmt::Display, rc::Rc, str::FromStr};
+use thiserror::Error;
+
+pub fn base_convert_class() -> Class {
+    cached_class(|| Class {
+        name: "base_convert".into(),
+        nodes: vec![Rc::new(BaseConvertNode(BaseConvertVariant {
+            direction: ConvertDirection::To,
+            base: Base::Hex,
+        })) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn number_theory_class() -> Class {
+    cached_class(|| Class {
+        name: "number_theory".into(),
+        nodes: vec![Rc::new(NumberTheoryNode(NumberTheoryOp::Gcd)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn parse_int_class() -> Class {
+    cached_class(|| Class {
+        name: "parse_int".into(),
+        nodes: vec![Rc::new(ParseIntNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ConvertDirection {
+    /// number -> string
+    To,
+    /// string -> number
+    From,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Base {
+    Hex,
+    Binary,
+    Octal,
+}
+
+impl Base {
+    fn radix(&self) -> u32 {
+        match self {
+            Self::Hex => 16,
+            Self::Binary => 2,
+            Self::Octal => 8,
+        }
+    }
+
+    fn format(&self, n: i64) -> String {
+        let sign = if n < 0 { "-" } else { "" };
+        let n = n.unsigned_abs();
+        match self {
+            Self::Hex => format!("{sign}{n:x}"),
+            Self::Binary => format!("{sign}{n:b}"),
+            Self::Octal => format!("{sign}{n:o}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BaseConvertVariant {
+    direction: ConvertDirection,
+    base: Base,
+}
+
+impl Display for BaseConvertVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}-{}",
+            match self.direction {
+                ConvertDirection::To => "to",
+                ConvertDirection::From => "from",
+            },
+            match self.base {
+                Base::Hex => "hex",
+                Base::Binary => "binary",
+                Base::Octal => "octal",
+            }
+        )
+    }
+}
+
+impl FromStr for BaseConvertVariant {
+    type Err = BaseConvertVariantParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (direction, base) = s
+            .split_once('-')
+            .ok_or_else(|| BaseConvertVariantParseError::InvalidVariant(s.into()))?;
+        let direction = match direction {
+            "to" => ConvertDirection::To,
+            "from" => ConvertDirection::From,
+            d => return Err(BaseConvertVariantParseError::UnknownDirection(d.into())),
+        };
+        let base = match base {
+            "hex" => Base::Hex,
+            "binary" => Base::Binary,
+            "octal" => Base::Octal,
+            b => return Err(BaseConvertVariantParseError::UnknownBase(b.into())),
+        };
+        Ok(Self { direction, base })
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum BaseConvertVariantParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+    #[error("Invalid conversion direction: {0}")]
+    UnknownDirection(String),
+    #[error("Invalid base: {0}")]
+    UnknownBase(String),
+}
+
+/// Converts integer part of a number to a string in another base and back. Branches to 1 if
+/// the string contains invalid digits.
+#[derive(Debug, Clone)]
+pub struct BaseConvertNode(BaseConvertVariant);
+
+impl Node for BaseConvertNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        match self.0.direction {
+            ConvertDirection::To => {
+                let res = self.0.base.format(context.get_number(0)? as i64);
+                context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+                Ok(0)
+            }
+            ConvertDirection::From => {
+                match i64::from_str_radix(&context.get_string(0)?, self.0.base.radix()) {
+                    Ok(n) => {
+                        context.set_outputs(vec![Rc::new(n as f64) as Rc<dyn Object>]);
+                        Ok(0)
+                    }
+                    Err(_) => Ok(1),
+                }
+            }
+        }
+    }
+
+    fn class(&self) -> Class {
+        base_convert_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![
+            "to-hex".into(),
+            "to-binary".into(),
+            "to-octal".into(),
+            "from-hex".into(),
+            "from-binary".into(),
+            "from-octal".into(),
+        ]
+    }
+
+    fn variant_descriptions(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        vec![
+            (
+                "to-hex".into(),
+                "Integer part of a number as hexadecimal string".into(),
+            ),
+            (
+                "to-binary".into(),
+                "Integer part of a number as binary string".into(),
+            ),
+            (
+                "to-octal".into(),
+                "Integer part of a number as octal string".into(),
+            ),
+            (
+                "from-hex".into(),
+                "Parse hexadecimal string into a number".into(),
+            ),
+            (
+                "from-binary".into(),
+                "Parse binary string into a number".into(),
+            ),
+            (
+                "from-octal".into(),
+                "Parse octal string into a number".into(),
+            ),
+        ]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        let class = match self.0.direction {
+            ConvertDirection::To => number_class(),
+            ConvertDirection::From => string_class(),
+        };
+        vec![InputSocket {
+            class,
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        let class = match self.0.direction {
+            ConvertDirection::To => string_class(),
+            ConvertDirection::From => number_class(),
+        };
+        vec![OutputSocket { class }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberTheoryOp {
+    Gcd,
+    Lcm,
+    ModPow,
+}
+
+impl NumberTheoryOp {
+    const ALL: [Self; 3] = [Self::Gcd, Self::Lcm, Self::ModPow];
+}
+
+impl Display for NumberTheoryOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Gcd => "gcd",
+                Self::Lcm => "lcm",
+                Self::ModPow => "modpow",
+            }
+        )
+    }
+}
+
+impl FromStr for NumberTheoryOp {
+    type Err = NumberTheoryOpParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "gcd" => Ok(Self::Gcd),
+            "lcm" => Ok(Self::Lcm),
+            "modpow" => Ok(Self::ModPow),
+            s => Err(NumberTheoryOpParseError::InvalidVariant(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum NumberTheoryOpParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// `base` to the power of `exp` modulo `modulus`, in the range from 0 to the modulus
+fn modpow(base: i64, mut exp: u64, modulus: u64) -> u64 {
+    let modulus = modulus as u128;
+    let mut base = (base as i128).rem_euclid(modulus as i128) as u128;
+    let mut res = 1 % modulus;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            res = res * base % modulus;
+        }
+        base = base * base % modulus;
+        exp >>= 1;
+    }
+    res as u64
+}
+
+/// Integer operations on the integer parts of numbers. `gcd` and `lcm` take two numbers and work
+/// on their absolute values: gcd with 0 is the other number and lcm with 0 is 0, lcm saturates
+/// instead of overflowing. `modpow` takes a base, an exponent and a modulus, branching to 1 if the
+/// exponent is negative or the modulus is 0.
+#[derive(Debug, Clone)]
+pub struct NumberTheoryNode(NumberTheoryOp);
+
+impl Node for NumberTheoryNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (a, b) = (context.get_number(0)? as i64, context.get_number(1)? as i64);
+        let res = match self.0 {
+            NumberTheoryOp::Gcd => gcd(a.unsigned_abs(), b.unsigned_abs()),
+            NumberTheoryOp::Lcm => {
+                let (a, b) = (a.unsigned_abs(), b.unsigned_abs());
+                if a == 0 || b == 0 {
+                    0
+                } else {
+                    (a / gcd(a, b)).saturating_mul(b)
+                }
+            }
+            NumberTheoryOp::ModPow => {
+                let modulus = context.get_number(2)? as i64;
+                if b < 0 || modulus == 0 {
+                    return Ok(1);
+                }
+                modpow(a, b as u64, modulus.unsigned_abs())
+            }
+        };
+        context.set_outputs(vec![Rc::new(res as f64) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        number_theory_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        NumberTheoryOp::ALL
+            .iter()
+            .map(|v| v.to_string().into())
+            .collect()
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        let amount = match self.0 {
+            NumberTheoryOp::Gcd | NumberTheoryOp::Lcm => 2,
+            NumberTheoryOp::ModPow => 3,
+        };
+        vec![
+            InputSocket {
+                class: number_class(),
+                default: None,
+            };
+            amount
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Parse a string as an integer in a radix from 2 to 36, 10 by default. On failure, branches to 1
+/// with null as the number and an error describing what is wrong with the string or radix.
+#[derive(Debug, Clone)]
+pub struct ParseIntNode;
+
+impl Node for ParseIntNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (text, radix) = (context.get_string(0)?, context.get_number(1)?);
+        let res = if radix.fract() == 0.0 && (2.0..=36.0).contains(&radix) {
+            i64::from_str_radix(&text, radix as u32).map_err(|e| e.to_string())
+        } else {
+            Err(format!(
+                "radix must be an integer from 2 to 36, got {radix}"
+            ))
+        };
+        match res {
+            Ok(n) => {
+                context.set_outputs(vec![
+                    Rc::new(n as f64) as Rc<dyn Object>,
+                    Rc::new(NullType) as Rc<dyn Object>,
+                ]);
+                Ok(0)
+            }
+            Err(message) => {
+                context.set_outputs(vec![
+                    Rc::new(NullType) as Rc<dyn Object>,
+                    Rc::new(ErrorObject::new("parse", message)) as Rc<dyn Object>,
+                ]);
+                Ok(1)
+            }
+        }
+    }
+
+    fn class(&self) -> Class {
+        parse_int_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["parse_int".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "parse_int".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: None,
+            },
+            InputSocket {
+                class: number_class(),
+                default: Some("10".into()),
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![
+            OutputSocket {
+                class: number_class(),
+            },
+            OutputSocket {
+                class: error_class(),
+            },
+        ]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{run_node, TestProgram};
+
+    #[test]
+    fn hex_round_trip() {
+        let hex = run_node("base_convert", "to-hex", &[("number", "255")], 1).unwrap();
+        assert_eq!(hex, ["ff"]);
+        let number = run_node("base_convert", "from-hex", &[("string", &hex[0])], 1).unwrap();
+        assert_eq!(number, ["255"]);
+    }
+
+    #[test]
+    fn malformed_hex_is_an_error() {
+        assert_eq!(
+            run_node("base_convert", "from-hex", &[("string", "fg")], 1),
+            None
+        );
+    }
+
+    /// Parse a string, returning the number on success and the error otherwise. The error branch
+    /// ends at an end node with two inputs, so the branch is told apart by the amount of outputs.
+    fn parse_int(text: &str, radix: Option<&str>) -> Result<String, String> {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "parse_int", "parse_int")
+            .const_input(1, 0, text)
+            .end(9, 1)
+            .chain(&[0, 1, 9])
+            .connect(1, 0, 9, 0)
+            .end(8, 2)
+            .edge(1, 1, 8)
+            .connect(1, 0, 8, 0)
+            .connect(1, 1, 8, 1);
+        if let Some(radix) = radix {
+            program.const_input(1, 1, radix);
+        }
+        match &program.run_strings()[..] {
+            [number] => Ok(number.clone()),
+            [null, error] if null == "null" => Err(error.clone()),
+            outputs => panic!("unexpected outputs {outputs:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_in_radix() {
+        assert_eq!(parse_int("ff", Some("16")), Ok("255".into()));
+        assert_eq!(parse_int("101", Some("2")), Ok("5".into()));
+        assert_eq!(parse_int("-42", None), Ok("-42".into()));
+    }
+
+    #[test]
+    fn invalid_digit_is_an_error() {
+        assert_eq!(
+            parse_int("12", Some("2")),
+            Err("parse: invalid digit found in string".into())
+        );
+        assert_eq!(
+            parse_int("1", Some("37")),
+            Err("parse: radix must be an integer from 2 to 36, got 37".into())
+        );
+    }
+
+    fn number_theory(variant: &str, inputs: &[&str]) -> Option<Vec<String>> {
+        let inputs: Vec<_> = inputs.iter().map(|input| ("number", *input)).collect();
+        run_node("number_theory", variant, &inputs, 1)
+    }
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(number_theory("gcd", &["12", "18"]).unwrap(), ["6"]);
+        assert_eq!(number_theory("lcm", &["4", "6"]).unwrap(), ["12"]);
+        assert_eq!(number_theory("gcd", &["0", "7"]).unwrap(), ["7"]);
+        assert_eq!(number_theory("gcd", &["-12", "0"]).unwrap(), ["12"]);
+        assert_eq!(number_theory("lcm", &["0", "7"]).unwrap(), ["0"]);
+    }
+
+    #[test]
+    fn modpow() {
+        assert_eq!(
+            number_theory("modpow", &["4", "13", "497"]).unwrap(),
+            ["445"]
+        );
+        assert_eq!(number_theory("modpow", &["-2", "3", "5"]).unwrap(), ["2"]);
+        assert_eq!(number_theory("modpow", &["2", "-1", "5"]), None);
+        assert_eq!(number_theory("modpow", &["2", "3", "0"]), None);
+    }
+}
@@ -0,0 +1,229 @@
+#[cfg(feature = "uuid")]
+use super::string_class;
+use super::{cached_class, number_class};
+#[cfg(feature = "uuid")]
+use crate::object::Object;
+use crate::{
+    class::Class,
+    node::Node,
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, rc::Rc, time::Duration};
+
+pub fn sleep_class() -> Class {
+    cached_class(|| Class {
+        name: "sleep".into(),
+        nodes: vec![Rc::new(SleepNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+#[cfg(feature = "uuid")]
+pub fn uuid_class() -> Class {
+    cached_class(|| Class {
+        name: "uuid".into(),
+        nodes: vec![Rc::new(UuidNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Pause execution for a number of milliseconds. Execution can still be aborted while waiting.
+/// Branches to 1 if the duration is negative or not finite.
+#[derive(Debug, Clone)]
+pub struct SleepNode;
+
+impl Node for SleepNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let millis = context.get_number(0)?;
+        let Ok(duration) = Duration::try_from_secs_f64(millis / 1000.0) else {
+            return Ok(1);
+        };
+        context.sleep(duration)?;
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        sleep_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["sleep".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "sleep".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: number_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Outputs a random (version 4) UUID in hyphenated form. Randomness comes from the executor, so
+/// the ids repeat between runs if the executor is seeded.
+#[cfg(feature = "uuid")]
+#[derive(Debug, Clone)]
+pub struct UuidNode;
+
+#[cfg(feature = "uuid")]
+impl Node for UuidNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let random = (u128::from(context.random_u64()) << 64) | u128::from(context.random_u64());
+        let res = uuid::Builder::from_random_bytes(random.to_le_bytes())
+            .into_uuid()
+            .hyphenated()
+            .to_string();
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        uuid_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["uuid".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "uuid".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{test_utils::TestProgram, AbortHandle, Clock, ExecutionError};
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    /// Clock that doesn't wait, but counts the waited time and aborts execution after a while
+    #[derive(Debug)]
+    struct AbortingClock {
+        waited: Cell<Duration>,
+        abort_after: Duration,
+        handle: AbortHandle,
+    }
+
+    impl Clock for AbortingClock {
+        fn sleep(&self, duration: Duration) {
+            self.waited.set(self.waited.get() + duration);
+            if self.waited.get() >= self.abort_after {
+                self.handle.abort();
+            }
+        }
+    }
+
+    fn sleep_program(millis: &str) -> TestProgram {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "sleep", "sleep")
+            .const_input(1, 0, millis)
+            .end(9, 0)
+            .chain(&[0, 1, 9]);
+        program
+    }
+
+    #[test]
+    fn sleep_is_aborted_while_waiting() {
+        let mut executor = sleep_program("60000").executor();
+        let clock = Rc::new(AbortingClock {
+            waited: Cell::default(),
+            abort_after: Duration::from_millis(30),
+            handle: executor.abort_handle(),
+        });
+        executor.set_clock(Rc::clone(&clock) as Rc<dyn Clock>);
+        assert!(matches!(
+            executor.start_execution(true),
+            Err(ExecutionError::Aborted)
+        ));
+        assert_eq!(clock.waited.get(), Duration::from_millis(30));
+    }
+
+    #[test]
+    fn sleep_waits_for_the_duration() {
+        let mut executor = sleep_program("25").executor();
+        let clock = Rc::new(AbortingClock {
+            waited: Cell::default(),
+            abort_after: Duration::MAX,
+            handle: executor.abort_handle(),
+        });
+        executor.set_clock(Rc::clone(&clock) as Rc<dyn Clock>);
+        executor.start_execution(true).unwrap();
+        assert_eq!(clock.waited.get(), Duration::from_millis(25));
+    }
+
+    /// Ids made by two uuid nodes in one run
+    #[cfg(feature = "uuid")]
+    fn two_uuids(seed: Option<u64>) -> Vec<String> {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "uuid", "uuid")
+            .node(2, "uuid", "uuid")
+            .end(9, 2)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 9, 0)
+            .connect(2, 0, 9, 1);
+        let mut executor = program.executor();
+        if let Some(seed) = seed {
+            executor.set_seed(seed);
+        }
+        executor.start_execution(true).unwrap();
+        let output = executor.final_output().unwrap();
+        output.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn uuids_are_different() {
+        let ids = two_uuids(None);
+        assert_ne!(ids[0], ids[1]);
+        for id in ids {
+            let id: uuid::Uuid = id.parse().unwrap();
+            assert_eq!(id.get_version_num(), 4);
+        }
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn seeded_uuids_repeat() {
+        let ids = two_uuids(Some(7));
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(two_uuids(Some(7)), ids);
+        assert_ne!(two_uuids(Some(8)), ids);
+    }
+}
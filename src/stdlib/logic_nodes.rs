@@ -0,0 +1,157 @@
+use super::{any_class, cached_class, null_class};
+use crate::{
+    class::Class,
+    node::{Node, VariantError},
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, fmt::Display, num::ParseIntError, rc::Rc, str::FromStr};
+use thiserror::Error;
+
+pub fn coalesce_class() -> Class {
+    cached_class(|| Class {
+        name: "coalesce".into(),
+        nodes: vec![Rc::new(CoalesceNode(CoalesceVariant {
+            skip_empty: false,
+            amount: 2,
+        })) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CoalesceVariant {
+    /// Also skip values that are false by `as_bool`: empty strings, arrays and dicts, 0, false
+    skip_empty: bool,
+    amount: usize,
+}
+
+impl Display for CoalesceVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.skip_empty {
+            write!(f, "coalesce-empty-{}", self.amount)
+        } else {
+            write!(f, "coalesce-{}", self.amount)
+        }
+    }
+}
+
+impl FromStr for CoalesceVariant {
+    type Err = CoalesceVariantParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .strip_prefix("coalesce-")
+            .ok_or_else(|| CoalesceVariantParseError::InvalidVariant(s.into()))?;
+        let (skip_empty, amount) = match rest.strip_prefix("empty-") {
+            Some(amount) => (true, amount),
+            None => (false, rest),
+        };
+        Ok(Self {
+            skip_empty,
+            amount: amount.parse()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum CoalesceVariantParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+    #[error("Failed to parse amount: {0}")]
+    AmountParseError(#[from] ParseIntError),
+}
+
+/// Outputs the first input that is not null. The `coalesce-empty-<amount>` variants also skip
+/// empty values, that is values that are false by `as_bool`. Branches to 1 if every input is
+/// skipped.
+#[derive(Debug, Clone)]
+pub struct CoalesceNode(CoalesceVariant);
+
+impl Node for CoalesceNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let found = context
+            .get_inputs()?
+            .into_iter()
+            .find(|value| value.class() != null_class() && (!self.0.skip_empty || value.as_bool()));
+        let Some(value) = found else {
+            return Ok(1);
+        };
+        context.set_outputs(vec![value]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        coalesce_class()
+    }
+
+    /// Format: coalesce-<amount> or coalesce-empty-<amount>
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: coalesce-<amount> or coalesce-empty-<amount>
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    /// Format: coalesce-<amount> or coalesce-empty-<amount>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            self.0.amount
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::run_node;
+
+    fn coalesce(variant: &str, inputs: &[(&str, &str)]) -> Option<String> {
+        Some(run_node("coalesce", variant, inputs, 1)?[0].clone())
+    }
+
+    #[test]
+    fn first_not_null() {
+        let inputs = [("null", "null"), ("string", ""), ("number", "2")];
+        assert_eq!(coalesce("coalesce-3", &inputs).unwrap(), "");
+        assert_eq!(coalesce("coalesce-empty-3", &inputs).unwrap(), "2");
+    }
+
+    #[test]
+    fn all_null() {
+        let inputs = [("null", "null"), ("null", "null")];
+        assert_eq!(coalesce("coalesce-2", &inputs), None);
+        let inputs = [("null", "null"), ("number", "0"), ("array", "[]")];
+        assert_eq!(coalesce("coalesce-empty-3", &inputs), None);
+    }
+}
@@ -1,31 +1,84 @@
 use crate::{class::Class, module::ModulePath, Plugin};
-use std::collections::HashMap;
+use std::{any::TypeId, cell::RefCell, collections::HashMap};
 
 mod any_type;
 mod array_type;
 mod bool_type;
+mod cast_node;
+mod compare_nodes;
+mod construct_node;
 mod dict_type;
+mod error_type;
+mod field_nodes;
 mod flow_nodes;
 mod if_node;
+mod instance_type;
+mod integer_type;
+mod io_nodes;
+mod logic_nodes;
+mod native_call_node;
 mod nop_node;
+mod null_type;
 mod number_type;
 mod print_node;
+mod reflect_nodes;
+mod set_type;
+mod stack_queue_nodes;
+mod string_nodes;
 mod string_type;
 mod subroutine;
+mod tee_node;
+mod type_switch_node;
 mod variable_node;
+mod yield_node;
 
 pub use any_type::*;
 pub use array_type::*;
 pub use bool_type::*;
+pub use cast_node::*;
+pub use compare_nodes::*;
+pub use construct_node::*;
 pub use dict_type::*;
+pub use error_type::*;
+pub use field_nodes::*;
 pub use flow_nodes::*;
 pub use if_node::*;
+pub use instance_type::*;
+pub use integer_type::*;
+pub use io_nodes::*;
+pub use logic_nodes::*;
+pub use native_call_node::*;
 pub use nop_node::*;
+pub use null_type::*;
 pub use number_type::*;
 pub use print_node::*;
+pub use reflect_nodes::*;
+pub use set_type::*;
+pub use stack_queue_nodes::*;
+pub use string_nodes::*;
 pub use string_type::*;
 pub use subroutine::*;
+pub use tee_node::*;
+pub use type_switch_node::*;
 pub use variable_node::*;
+pub use yield_node::*;
+
+thread_local! {
+    static CLASS_CACHE: RefCell<HashMap<TypeId, Class>> = RefCell::default();
+}
+
+/// Construct a class once per thread and return clones of it afterwards, so that repeated calls
+/// share default nodes instead of allocating new ones. Every call site has its own closure type,
+/// which is used as the cache key.
+pub(crate) fn cached_class<F: FnOnce() -> Class + 'static>(init: F) -> Class {
+    let key = TypeId::of::<F>();
+    if let Some(class) = CLASS_CACHE.with(|cache| cache.borrow().get(&key).cloned()) {
+        return class;
+    }
+    let class = init();
+    CLASS_CACHE.with(|cache| cache.borrow_mut().insert(key, class.clone()));
+    class
+}
 
 pub struct StdPlugin;
 
@@ -34,21 +87,131 @@ impl Plugin for StdPlugin {
         [
             any_class(),
             array_class(),
+            range_class(),
+            destructure_class(),
+            array_stats_class(),
+            pack_class(),
+            unpack_class(),
+            enumerate_class(),
+            max_len_class(),
+            choice_class(),
+            shuffle_class(),
+            unique_class(),
+            vector_op_class(),
+            flatten_class(),
+            find_index_class(),
+            find_class(),
             bool_class(),
+            to_bool_class(),
+            cast_class(),
+            equals_class(),
+            not_equals_class(),
+            deep_equals_class(),
+            min_class(),
+            max_class(),
+            between_class(),
+            construct_class(),
+            default_of_class(),
             dict_class(),
+            dict_to_kv_class(),
+            kv_to_dict_class(),
+            dict_sorted_keys_class(),
+            dict_sorted_values_class(),
+            dict_partition_class(),
+            dict_entries_class(),
+            from_entries_class(),
+            group_by_class(),
+            error_class(),
+            len_class(),
+            spread_class(),
+            contains_class(),
+            field_path_class(),
             start_node_class(),
             end_node_class(),
+            guard_class(),
+            return_class(),
             if_node_class(),
+            new_instance_class(),
+            method_call_class(),
+            base_convert_class(),
+            number_theory_class(),
+            parse_int_class(),
+            sleep_class(),
+            #[cfg(feature = "uuid")]
+            uuid_class(),
+            native_call_class(),
+            coalesce_class(),
             nop_node_class(),
+            null_class(),
             number_class(),
+            to_number_class(),
+            is_finite_class(),
+            is_nan_class(),
+            default_if_not_finite_class(),
+            lerp_class(),
+            clamp_class(),
+            round_class(),
+            number_format_class(),
             print_class(),
+            stack_depth_class(),
+            caller_class(),
+            current_program_class(),
+            current_node_id_class(),
+            node_variant_class(),
+            start_node_of_class(),
+            recursion_guard_class(),
+            inspect_class(),
+            hash_class(),
+            set_class(),
+            set_add_class(),
+            set_contains_class(),
+            set_operation_class(),
+            stack_push_class(),
+            stack_pop_class(),
+            queue_enqueue_class(),
+            queue_dequeue_class(),
+            join_class(),
+            string_search_class(),
+            string_extract_class(),
+            string_slice_class(),
+            string_length_class(),
+            template_class(),
+            repeat_class(),
+            #[cfg(feature = "regex")]
+            regex_class(),
+            #[cfg(feature = "base64")]
+            base64_class(),
             string_class(),
             subroutine_class(),
+            subroutine_build_class(),
+            retry_class(),
+            tee_class(),
+            type_switch_class(),
             variable_get_class(),
             variable_set_class(),
+            variable_swap_class(),
+            variable_rotate_class(),
+            accumulate_class(),
+            destructure_to_vars_class(),
+            dump_vars_class(),
+            yield_node_class(),
         ]
         .into_iter()
         .map(|cl| (ModulePath(vec!["std".into()], cl.name.clone()), cl))
         .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn classes_share_default_nodes() {
+        let (first, second) = (if_node_class(), if_node_class());
+        assert_eq!(first, second);
+        assert!(Rc::ptr_eq(&first.nodes[0], &second.nodes[0]));
+        assert_ne!(if_node_class(), nop_node_class());
+    }
+}
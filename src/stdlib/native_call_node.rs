@@ -0,0 +1,141 @@
+use super::{any_class, cached_class};
+use crate::{
+    class::Class,
+    node::{Node, VariantError},
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, rc::Rc};
+
+pub fn native_call_class() -> Class {
+    cached_class(|| Class {
+        name: "native".into(),
+        nodes: vec![Rc::new(NativeCallNode {
+            name: String::new(),
+            inputs: 1,
+            outputs: 1,
+        }) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Call a function registered by the host with `Executor::register_native`, passing inputs to it
+/// and outputting whatever it returns. The amount of inputs and outputs is part of the variant,
+/// since the function is only looked up when the node is executed. Branches to 1 if no function
+/// with that name is registered.
+#[derive(Debug, Clone)]
+pub struct NativeCallNode {
+    name: String,
+    inputs: usize,
+    outputs: usize,
+}
+
+impl Node for NativeCallNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let args = context.get_inputs()?;
+        let Some(outputs) = context.call_native(&self.name, args) else {
+            return Ok(1);
+        };
+        context.set_outputs(outputs);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        native_call_class()
+    }
+
+    /// Format: native:<name>:<inputs>:<outputs>
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: native:<name>:<inputs>:<outputs>
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("native:{}:{}:{}", self.name, self.inputs, self.outputs).into()
+    }
+
+    /// Format: native:<name>:<inputs>:<outputs>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        let format_error =
+            || VariantError::new(variant, "expected `native:<name>:<inputs>:<outputs>`");
+        let (name, counts) = variant
+            .strip_prefix("native:")
+            .and_then(|rest| rest.split_once(':'))
+            .ok_or_else(format_error)?;
+        let (inputs, outputs) = counts.split_once(':').ok_or_else(format_error)?;
+        self.inputs = inputs.parse().map_err(|e| VariantError::new(variant, e))?;
+        self.outputs = outputs.parse().map_err(|e| VariantError::new(variant, e))?;
+        self.name = name.into();
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None,
+            };
+            self.inputs
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }; self.outputs]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        object::Object,
+        test_utils::{run_node, TestProgram},
+    };
+    use std::rc::Rc;
+
+    #[test]
+    fn call_uppercase() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "string", "shout")
+            .node(2, "native", "native:uppercase:1:1")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(1, 0, 2, 0)
+            .connect(2, 0, 9, 0);
+        let mut executor = program.executor();
+        executor.register_native(
+            "uppercase",
+            Box::new(|args| {
+                args.iter()
+                    .map(|arg| Rc::new(arg.as_string().to_uppercase()) as Rc<dyn Object>)
+                    .collect()
+            }),
+        );
+        executor.start_execution(true).unwrap();
+        let output = executor.final_output().unwrap();
+        assert_eq!(output[0].to_string(), "SHOUT");
+    }
+
+    #[test]
+    fn unknown_function() {
+        let args = [("string", "shout")];
+        assert_eq!(run_node("native", "native:uppercase:1:1", &args, 1), None);
+    }
+}
@@ -1,17 +1,18 @@
+use super::cached_class;
 use crate::{
     class::Class,
     node::Node,
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
 use std::{borrow::Cow, rc::Rc};
 
 pub fn nop_node_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "nop".into(),
         nodes: vec![Rc::new(NopNode) as Rc<dyn Node>],
         obj_from_str: None,
-    }
+    })
 }
 
 /// Does nothing. Literal NOP. The easiest node to implement
@@ -19,8 +20,8 @@ pub fn nop_node_class() -> Class {
 pub struct NopNode;
 
 impl Node for NopNode {
-    fn execute(&self, _context: &mut ExecutionContext) -> usize {
-        0
+    fn execute(&self, _context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -0,0 +1,109 @@
+use super::cached_class;
+use crate::{
+    class::Class,
+    node::Node,
+    object::{Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd},
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use stainless_script_derive::{ObjectEq, ObjectOrd};
+use std::{borrow::Cow, fmt::Display, rc::Rc, str::FromStr};
+use thiserror::Error;
+
+pub fn null_class() -> Class {
+    cached_class(|| Class {
+        name: "null".into(),
+        nodes: vec![Rc::new(NullNode) as Rc<dyn Node>],
+        obj_from_str: Some(<NullType as ObjectFromStr>::from_str),
+    })
+}
+
+/// Absence of a value. All nulls are equal to each other.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ObjectEq, ObjectOrd)]
+pub struct NullType;
+
+impl FromStr for NullType {
+    type Err = NullParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" | "null" => Ok(NullType),
+            s => Err(NullParseError(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+#[error("Not a null: {0}")]
+pub struct NullParseError(String);
+
+impl Display for NullType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "null")
+    }
+}
+
+impl Object for NullType {
+    fn class(&self) -> Class {
+        null_class()
+    }
+
+    fn as_number(&self) -> f64 {
+        0.0
+    }
+
+    fn as_bool(&self) -> bool {
+        false
+    }
+}
+
+impl ObjectPartialEq for NullType {
+    fn eq(&self, other: Rc<dyn Object>) -> bool {
+        other.class() == self.class()
+    }
+}
+
+impl ObjectPartialOrd for NullType {
+    fn partial_cmp(&self, other: Rc<dyn Object>) -> Option<std::cmp::Ordering> {
+        (other.class() == self.class()).then_some(std::cmp::Ordering::Equal)
+    }
+}
+
+/// Outputs a null
+#[derive(Debug, Clone)]
+pub struct NullNode;
+
+impl Node for NullNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        context.set_outputs(vec![Rc::new(NullType) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        null_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["null".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "null".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: null_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
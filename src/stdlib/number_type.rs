@@ -1,20 +1,89 @@
 use crate::{
     class::Class,
-    node::Node,
-    object::{Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd},
+    node::{Node, VariantError},
+    object::{
+        class_hash, Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd,
+    },
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
-use std::rc::Rc;
+use std::{fmt::Display, rc::Rc, str::FromStr};
+use thiserror::Error;
 
-use super::any_class;
+use super::{
+    any_class, bool_class, cached_class, error_class, string_class, ErrorObject, NullType,
+};
 
 pub fn number_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "number".into(),
         nodes: vec![Rc::new(NumberNode) as Rc<dyn Node>],
         obj_from_str: Some(<f64 as ObjectFromStr>::from_str),
-    }
+    })
+}
+
+pub fn to_number_class() -> Class {
+    cached_class(|| Class {
+        name: "to_number".into(),
+        nodes: vec![Rc::new(ToNumberNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn is_finite_class() -> Class {
+    cached_class(|| Class {
+        name: "is_finite".into(),
+        nodes: vec![Rc::new(IsFiniteNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn is_nan_class() -> Class {
+    cached_class(|| Class {
+        name: "is_nan".into(),
+        nodes: vec![Rc::new(IsNanNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn default_if_not_finite_class() -> Class {
+    cached_class(|| Class {
+        name: "default_if_not_finite".into(),
+        nodes: vec![Rc::new(DefaultIfNotFiniteNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn lerp_class() -> Class {
+    cached_class(|| Class {
+        name: "lerp".into(),
+        nodes: vec![Rc::new(LerpNode { clamped: false }) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn number_format_class() -> Class {
+    cached_class(|| Class {
+        name: "number_format".into(),
+        nodes: vec![Rc::new(NumberFormatNode { thousands: false }) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn round_class() -> Class {
+    cached_class(|| Class {
+        name: "round".into(),
+        nodes: vec![Rc::new(RoundNode(RoundMode::Round)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn clamp_class() -> Class {
+    cached_class(|| Class {
+        name: "clamp".into(),
+        nodes: vec![Rc::new(ClampNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
 }
 
 impl Object for f64 {
@@ -37,6 +106,12 @@ impl Object for f64 {
             _ => panic!("Unknown field: {field}"),
         }
     }
+
+    /// Zeros of both signs are equal, so they hash the same
+    fn object_hash(&self) -> u64 {
+        let number = if *self == 0.0 { 0.0 } else { *self };
+        class_hash("number", number.to_string().bytes())
+    }
 }
 
 impl ObjectPartialEq for f64 {
@@ -71,10 +146,10 @@ impl ObjectOrd for f64 {
 pub struct NumberNode;
 
 impl Node for NumberNode {
-    fn execute(&self, context: &mut ExecutionContext) -> usize {
-        let res = context.get_inputs()[0].as_number();
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = context.get_object(0)?.as_number();
         context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
-        0
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -92,7 +167,579 @@ impl Node for NumberNode {
     fn set_variant(&mut self, _variant: &str) {}
 
     fn inputs(&self) -> Vec<InputSocket> {
-        vec![InputSocket { class: any_class() }]
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Convert an object to a number. Strings are parsed, ignoring surrounding whitespace, bools are 1
+/// or 0 and null is 0. On failure, branches to 1 with null as the number and an error describing
+/// why, instead of panicking like the conversions done for sockets do.
+#[derive(Debug, Clone)]
+pub struct ToNumberNode;
+
+impl Node for ToNumberNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let input = context.get_object(0)?;
+        let res = match input.class().name.as_str() {
+            "number" | "bool" | "null" => Ok(input.as_number()),
+            "string" | "any" => {
+                let text = input.as_string();
+                text.trim()
+                    .parse::<f64>()
+                    .map_err(|e| ErrorObject::new("parse", format!("{e}: {text:?}")))
+            }
+            class => Err(ErrorObject::new(
+                "type",
+                format!("cannot convert {class} to number"),
+            )),
+        };
+        match res {
+            Ok(n) => {
+                context.set_outputs(vec![
+                    Rc::new(n) as Rc<dyn Object>,
+                    Rc::new(NullType) as Rc<dyn Object>,
+                ]);
+                Ok(0)
+            }
+            Err(error) => {
+                context.set_outputs(vec![
+                    Rc::new(NullType) as Rc<dyn Object>,
+                    Rc::new(error) as Rc<dyn Object>,
+                ]);
+                Ok(1)
+            }
+        }
+    }
+
+    fn class(&self) -> Class {
+        to_number_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["to_number".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "to_number".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![
+            OutputSocket {
+                class: number_class(),
+            },
+            OutputSocket {
+                class: error_class(),
+            },
+        ]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Whether a number is neither infinite nor NaN
+#[derive(Debug, Clone)]
+pub struct IsFiniteNode;
+
+impl Node for IsFiniteNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = context.get_number(0)?.is_finite();
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        is_finite_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["is_finite".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "is_finite".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: number_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: bool_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Whether a number is NaN
+#[derive(Debug, Clone)]
+pub struct IsNanNode;
+
+impl Node for IsNanNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = context.get_number(0)?.is_nan();
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        is_nan_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["is_nan".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "is_nan".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: number_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: bool_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Passes the number through if it is finite, otherwise outputs the fallback
+#[derive(Debug, Clone)]
+pub struct DefaultIfNotFiniteNode;
+
+impl Node for DefaultIfNotFiniteNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let value = context.get_number(0)?;
+        let res = if value.is_finite() {
+            value
+        } else {
+            context.get_number(1)?
+        };
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        default_if_not_finite_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["default_if_not_finite".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "default_if_not_finite".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: number_class(),
+                default: None,
+            },
+            InputSocket {
+                class: number_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Linear interpolation between start and end: `start + (end - start) * t`. The `clamped` variant
+/// limits t to the [0, 1] range, so the result never goes past start or end.
+#[derive(Debug, Clone)]
+pub struct LerpNode {
+    clamped: bool,
+}
+
+impl Node for LerpNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (start, end, mut t) = (
+            context.get_number(0)?,
+            context.get_number(1)?,
+            context.get_number(2)?,
+        );
+        if self.clamped {
+            t = t.clamp(0.0, 1.0);
+        }
+        let res = start + (end - start) * t;
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        lerp_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["lerp".into(), "clamped".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        if self.clamped { "clamped" } else { "lerp" }.into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.clamped = match variant {
+            "lerp" => false,
+            "clamped" => true,
+            _ => return Err(VariantError::new(variant, "expected `lerp` or `clamped`")),
+        };
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: number_class(),
+                default: None,
+            };
+            3
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Limit a number to the range from min to max, inclusive. Branches to 1 if min is greater than
+/// max or either of them is NaN.
+#[derive(Debug, Clone)]
+pub struct ClampNode;
+
+impl Node for ClampNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (value, min, max) = (
+            context.get_number(0)?,
+            context.get_number(1)?,
+            context.get_number(2)?,
+        );
+        if min > max || min.is_nan() || max.is_nan() {
+            return Ok(1);
+        }
+        context.set_outputs(vec![Rc::new(value.clamp(min, max)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        clamp_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["clamp".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        "clamp".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: number_class(),
+                default: None,
+            };
+            3
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Insert a comma between every three digits of the integer part of a formatted number
+fn group_thousands(formatted: &str) -> String {
+    let (sign, unsigned) = match formatted.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", formatted),
+    };
+    let (int_part, rest) = unsigned.split_at(unsigned.find('.').unwrap_or(unsigned.len()));
+    if !int_part.bytes().all(|b| b.is_ascii_digit()) {
+        return formatted.into();
+    }
+    let mut grouped = String::with_capacity(formatted.len() + int_part.len() / 3);
+    for (idx, digit) in int_part.chars().enumerate() {
+        if idx > 0 && (int_part.len() - idx) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{sign}{grouped}{rest}")
+}
+
+/// Format a number with a fixed amount of decimal places, rounding it. The `thousands` variant
+/// also separates groups of three digits in the integer part with commas. Branches to 1 if the
+/// amount of decimal places is not a whole number from 0 to 100.
+#[derive(Debug, Clone)]
+pub struct NumberFormatNode {
+    thousands: bool,
+}
+
+impl Node for NumberFormatNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (value, precision) = (context.get_number(0)?, context.get_number(1)?);
+        if precision.fract() != 0.0 || !(0.0..=100.0).contains(&precision) {
+            return Ok(1);
+        }
+        let mut res = format!("{:.*}", precision as usize, value);
+        if self.thousands {
+            res = group_thousands(&res);
+        }
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        number_format_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        vec!["fixed".into(), "thousands".into()]
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        if self.thousands { "thousands" } else { "fixed" }.into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.thousands = match variant {
+            "fixed" => false,
+            "thousands" => true,
+            _ => {
+                return Err(VariantError::new(
+                    variant,
+                    "expected `fixed` or `thousands`",
+                ))
+            }
+        };
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: number_class(),
+                default: None,
+            },
+            InputSocket {
+                class: number_class(),
+                default: Some("2".into()),
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RoundMode {
+    Floor,
+    Ceil,
+    /// Halves away from zero
+    Round,
+    Trunc,
+    /// Halves to the nearest even number
+    RoundHalfEven,
+}
+
+impl RoundMode {
+    const ALL: [Self; 5] = [
+        Self::Floor,
+        Self::Ceil,
+        Self::Round,
+        Self::Trunc,
+        Self::RoundHalfEven,
+    ];
+
+    fn apply(&self, n: f64) -> f64 {
+        match self {
+            Self::Floor => n.floor(),
+            Self::Ceil => n.ceil(),
+            Self::Round => n.round(),
+            Self::Trunc => n.trunc(),
+            Self::RoundHalfEven => n.round_ties_even(),
+        }
+    }
+}
+
+impl Display for RoundMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Floor => "floor",
+                Self::Ceil => "ceil",
+                Self::Round => "round",
+                Self::Trunc => "trunc",
+                Self::RoundHalfEven => "round-half-even",
+            }
+        )
+    }
+}
+
+impl FromStr for RoundMode {
+    type Err = RoundModeParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "floor" => Ok(Self::Floor),
+            "ceil" => Ok(Self::Ceil),
+            "round" => Ok(Self::Round),
+            "trunc" => Ok(Self::Trunc),
+            "round-half-even" => Ok(Self::RoundHalfEven),
+            s => Err(RoundModeParseError::InvalidVariant(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum RoundModeParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+/// Round a number to an integer. `round` rounds halves away from zero, `round-half-even` rounds
+/// them to the nearest even integer (2.5 becomes 2).
+#[derive(Debug, Clone)]
+pub struct RoundNode(RoundMode);
+
+impl Node for RoundNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = self.0.apply(context.get_number(0)?);
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        round_class()
+    }
+
+    fn variants(&self) -> Vec<std::borrow::Cow<'_, str>> {
+        RoundMode::ALL
+            .iter()
+            .map(|v| v.to_string().into())
+            .collect()
+    }
+
+    fn current_variant(&self) -> std::borrow::Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: number_class(),
+            default: None,
+        }]
     }
 
     fn outputs(&self) -> Vec<OutputSocket> {
@@ -105,3 +752,172 @@ impl Node for NumberNode {
         Rc::new(self.clone()) as Rc<dyn Node>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{run_node, TestProgram};
+
+    fn number_check(class: &str, number: &str) -> String {
+        run_node(class, class, &[("number", number)], 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn finite_and_nan_checks() {
+        assert_eq!(number_check("is_finite", "inf"), "false");
+        assert_eq!(number_check("is_finite", "NaN"), "false");
+        assert_eq!(number_check("is_finite", "1.5"), "true");
+        assert_eq!(number_check("is_nan", "inf"), "false");
+        assert_eq!(number_check("is_nan", "NaN"), "true");
+        assert_eq!(number_check("is_nan", "1.5"), "false");
+    }
+
+    #[test]
+    fn default_if_not_finite() {
+        let sanitize = |number| {
+            run_node(
+                "default_if_not_finite",
+                "default_if_not_finite",
+                &[("number", number), ("number", "0")],
+                1,
+            )
+            .unwrap()
+        };
+        assert_eq!(sanitize("inf"), ["0"]);
+        assert_eq!(sanitize("NaN"), ["0"]);
+        assert_eq!(sanitize("1.5"), ["1.5"]);
+    }
+
+    fn lerp(variant: &str, t: &str) -> String {
+        let inputs = [("number", "10"), ("number", "20"), ("number", t)];
+        run_node("lerp", variant, &inputs, 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn lerp_between_numbers() {
+        assert_eq!(lerp("lerp", "0"), "10");
+        assert_eq!(lerp("lerp", "0.5"), "15");
+        assert_eq!(lerp("lerp", "1"), "20");
+    }
+
+    #[test]
+    fn lerp_out_of_range() {
+        assert_eq!(lerp("lerp", "1.5"), "25");
+        assert_eq!(lerp("lerp", "-1"), "0");
+        assert_eq!(lerp("clamped", "1.5"), "20");
+        assert_eq!(lerp("clamped", "-1"), "10");
+    }
+
+    fn clamp(value: &str, min: &str, max: &str) -> Option<Vec<String>> {
+        let inputs = [("number", value), ("number", min), ("number", max)];
+        run_node("clamp", "clamp", &inputs, 1)
+    }
+
+    #[test]
+    fn clamp_into_range() {
+        assert_eq!(clamp("-5", "0", "10").unwrap(), ["0"]);
+        assert_eq!(clamp("5", "0", "10").unwrap(), ["5"]);
+        assert_eq!(clamp("15", "0", "10").unwrap(), ["10"]);
+    }
+
+    #[test]
+    fn inverted_bounds_are_an_error() {
+        assert_eq!(clamp("5", "10", "0"), None);
+    }
+
+    fn number_format(variant: &str, number: &str, precision: &str) -> Option<String> {
+        let inputs = [("number", number), ("number", precision)];
+        Some(run_node("number_format", variant, &inputs, 1)?[0].clone())
+    }
+
+    #[test]
+    fn fixed_decimals() {
+        assert_eq!(number_format("fixed", "3.14159", "2").unwrap(), "3.14");
+        assert_eq!(number_format("fixed", "2.5", "0").unwrap(), "2");
+        assert_eq!(number_format("fixed", "-7.6", "0").unwrap(), "-8");
+        assert_eq!(number_format("fixed", "1", "3").unwrap(), "1.000");
+    }
+
+    #[test]
+    fn thousands_separators() {
+        assert_eq!(
+            number_format("thousands", "1234567.891", "2").unwrap(),
+            "1,234,567.89"
+        );
+        assert_eq!(number_format("thousands", "-1000", "0").unwrap(), "-1,000");
+        assert_eq!(number_format("thousands", "999", "1").unwrap(), "999.0");
+        assert_eq!(number_format("thousands", "inf", "2").unwrap(), "inf");
+    }
+
+    #[test]
+    fn invalid_precision() {
+        assert_eq!(number_format("fixed", "1", "1.5"), None);
+        assert_eq!(number_format("fixed", "1", "-1"), None);
+    }
+
+    fn round(variant: &str, number: &str) -> String {
+        run_node("round", variant, &[("number", number)], 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn round_modes() {
+        let modes = ["floor", "ceil", "round", "trunc", "round-half-even"];
+        let rounded = |number| modes.map(|mode| round(mode, number));
+        assert_eq!(rounded("2.5"), ["2", "3", "3", "2", "2"]);
+        assert_eq!(rounded("3.5"), ["3", "4", "4", "3", "4"]);
+        assert_eq!(rounded("-2.5"), ["-3", "-2", "-3", "-2", "-2"]);
+    }
+
+    /// Convert to a number, returning the number on success and the error otherwise. The error
+    /// branch ends at an end node with two inputs, so the branch is told apart by the amount of
+    /// outputs.
+    fn to_number(class: &str, text: &str) -> Result<String, String> {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, class, text)
+            .node(2, "to_number", "to_number")
+            .connect(1, 0, 2, 0)
+            .end(9, 1)
+            .chain(&[0, 1, 2, 9])
+            .connect(2, 0, 9, 0)
+            .end(8, 2)
+            .edge(2, 1, 8)
+            .connect(2, 0, 8, 0)
+            .connect(2, 1, 8, 1);
+        match &program.run_strings()[..] {
+            [number] => Ok(number.clone()),
+            [null, error] if null == "null" => Err(error.clone()),
+            outputs => panic!("unexpected outputs {outputs:?}"),
+        }
+    }
+
+    #[test]
+    fn to_number_conversions() {
+        assert_eq!(to_number("string", " 42.5 "), Ok("42.5".into()));
+        assert_eq!(to_number("bool", "true"), Ok("1".into()));
+        assert_eq!(to_number("null", "null"), Ok("0".into()));
+        assert_eq!(to_number("number", "-3"), Ok("-3".into()));
+    }
+
+    #[test]
+    fn non_numeric_string_is_an_error() {
+        assert_eq!(
+            to_number("string", "hello"),
+            Err(r#"parse: invalid float literal: "hello""#.into())
+        );
+        assert_eq!(
+            to_number("array", "[1]"),
+            Err("type: cannot convert array to number".into())
+        );
+    }
+
+    #[test]
+    fn non_numeric_string_as_number() {
+        for class in ["string", "any"] {
+            let number = run_node("number", "from-object", &[(class, "hello")], 1);
+            assert_eq!(number.unwrap(), ["NaN"], "{class}");
+            let number = run_node("number", "from-object", &[(class, "2.5")], 1);
+            assert_eq!(number.unwrap(), ["2.5"], "{class}");
+        }
+    }
+}
@@ -1,22 +1,22 @@
-use super::any_class;
+use super::{any_class, cached_class};
 use crate::{
     class::Class,
-    node::Node,
+    node::{Node, VariantError},
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
 use std::{borrow::Cow, fmt::Display, num::ParseIntError, rc::Rc, str::FromStr};
 use thiserror::Error;
 
 pub fn print_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "print".into(),
         nodes: vec![Rc::new(Print(PrintVariant {
             ln: true,
             amount: 1,
         })) as Rc<dyn Node>],
         obj_from_str: None,
-    }
+    })
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -87,19 +87,19 @@ impl From<ParseIntError> for PrintVariantParseError {
 pub struct Print(PrintVariant);
 
 impl Node for Print {
-    fn execute(&self, context: &mut ExecutionContext) -> usize {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
         let to_print: String = context
-            .get_inputs()
+            .get_inputs()?
             .iter()
             .map(ToString::to_string)
             .collect::<Vec<String>>()
             .join(" ");
         if self.0.ln {
-            println!("{to_print}");
+            context.write_output(&format!("{to_print}\n"))?;
         } else {
-            print!("{to_print}");
+            context.write_output(&to_print)?;
         };
-        0
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -114,12 +114,30 @@ impl Node for Print {
         ]
     }
 
+    fn variant_descriptions(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        let custom = format!(
+            "{} {} inputs separated by spaces",
+            if self.0.ln { "Print line of" } else { "Print" },
+            self.0.amount
+        );
+        vec![
+            ("print".into(), "Print input without a newline".into()),
+            ("println".into(), "Print input followed by a newline".into()),
+            (Cow::Owned(self.0.to_string()), custom.into()),
+        ]
+    }
+
     fn current_variant(&self) -> Cow<'_, str> {
         self.0.to_string().into()
     }
 
     fn set_variant(&mut self, variant: &str) {
-        self.0 = variant.parse().unwrap()
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
     }
 
     fn accepts_arbitrary_variants(&self) -> bool {
@@ -127,7 +145,13 @@ impl Node for Print {
     }
 
     fn inputs(&self) -> Vec<InputSocket> {
-        vec![InputSocket { class: any_class() }; self.0.amount as usize]
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            self.0.amount as usize
+        ]
     }
 
     fn outputs(&self) -> Vec<OutputSocket> {
@@ -138,3 +162,30 @@ impl Node for Print {
         Rc::new(self.clone()) as Rc<dyn Node>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        node::AbsoluteNodeId,
+        test_utils::{main_path, TestProgram},
+    };
+
+    #[test]
+    fn print_variant_descriptions() {
+        let mut program = TestProgram::new();
+        program.node(1, "print", "println");
+        let signature = program
+            .executor()
+            .node_signature(&AbsoluteNodeId(main_path(), 1))
+            .unwrap();
+        assert_eq!(signature.variant, "println:1");
+        for variant in ["print", "println"] {
+            let (_, description) = signature
+                .variant_descriptions
+                .iter()
+                .find(|(v, _)| v == variant)
+                .unwrap();
+            assert!(!description.is_empty());
+        }
+    }
+}
@@ -0,0 +1,728 @@
+use super::{
+    any_class, array_class, array_items, cached_class, dict_class, dict_entries, number_class,
+    string_class, subroutine_class, NullType, Subroutine,
+};
+use crate::{
+    class::Class,
+    module::ModulePath,
+    node::{AbsoluteNodeId, Node, VariantError},
+    object::Object,
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, rc::Rc};
+
+pub fn stack_depth_class() -> Class {
+    cached_class(|| Class {
+        name: "stack_depth".into(),
+        nodes: vec![Rc::new(StackDepthNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn caller_class() -> Class {
+    cached_class(|| Class {
+        name: "caller".into(),
+        nodes: vec![Rc::new(CallerNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn current_program_class() -> Class {
+    cached_class(|| Class {
+        name: "current_program".into(),
+        nodes: vec![Rc::new(CurrentProgramNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn current_node_id_class() -> Class {
+    cached_class(|| Class {
+        name: "current_node_id".into(),
+        nodes: vec![Rc::new(CurrentNodeIdNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn node_variant_class() -> Class {
+    cached_class(|| Class {
+        name: "node_variant".into(),
+        nodes: vec![Rc::new(NodeVariantNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn start_node_of_class() -> Class {
+    cached_class(|| Class {
+        name: "start_node_of".into(),
+        nodes: vec![Rc::new(StartNodeOfNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn recursion_guard_class() -> Class {
+    cached_class(|| Class {
+        name: "recursion_guard".into(),
+        nodes: vec![Rc::new(RecursionGuardNode::default()) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn inspect_class() -> Class {
+    cached_class(|| Class {
+        name: "inspect".into(),
+        nodes: vec![Rc::new(InspectNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn hash_class() -> Class {
+    cached_class(|| Class {
+        name: "hash".into(),
+        nodes: vec![Rc::new(HashNode { as_number: false }) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Outputs how many frames are currently on the execution stack
+#[derive(Debug, Clone)]
+pub struct StackDepthNode;
+
+impl Node for StackDepthNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let depth = context.stack_depth() as f64;
+        context.set_outputs(vec![Rc::new(depth) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        stack_depth_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["stack_depth".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "stack_depth".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Outputs the id of the node that called current subroutine, or null if there is no caller
+#[derive(Debug, Clone)]
+pub struct CallerNode;
+
+impl Node for CallerNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let caller = context
+            .caller_node()
+            .map(|id| Rc::new(id.to_string()) as Rc<dyn Object>)
+            .unwrap_or_else(|| Rc::new(NullType) as Rc<dyn Object>);
+        context.set_outputs(vec![caller]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        caller_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["caller".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "caller".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Outputs the path of the program this node is placed in
+#[derive(Debug, Clone)]
+pub struct CurrentProgramNode;
+
+impl Node for CurrentProgramNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = context.current_node_id().0.to_string();
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        current_program_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["current_program".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "current_program".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Outputs the full id of this node, in `program@id` form
+#[derive(Debug, Clone)]
+pub struct CurrentNodeIdNode;
+
+impl Node for CurrentNodeIdNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = context.current_node_id().to_string();
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        current_node_id_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["current_node_id".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "current_node_id".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Outputs the current variant of a node given its full id in `program@id` form. Branches to 1 if
+/// the id is malformed or there is no such node.
+#[derive(Debug, Clone)]
+pub struct NodeVariantNode;
+
+impl Node for NodeVariantNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let Ok(node_id) = context.get_string(0)?.parse::<AbsoluteNodeId>() else {
+            return Ok(1);
+        };
+        let Some(node) = context.get_node(&node_id) else {
+            return Ok(1);
+        };
+        let res = node.current_variant().into_owned();
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        node_variant_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["node_variant".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "node_variant".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: string_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Outputs the full id of a start node, looked up by program path and start node name. Together
+/// with `subroutine_build` this allows calling entry points of other programs. Branches to 1 if
+/// the program is not loaded or has no such start node.
+#[derive(Debug, Clone)]
+pub struct StartNodeOfNode;
+
+impl Node for StartNodeOfNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (program, name) = (context.get_string(0)?, context.get_string(1)?);
+        let Ok(program) = program.parse::<ModulePath>() else {
+            return Ok(1);
+        };
+        let Some(node_id) = context.get_start_node(&program, &name) else {
+            return Ok(1);
+        };
+        context.set_outputs(vec![Rc::new(node_id.to_string()) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        start_node_of_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["start_node_of".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "start_node_of".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: None,
+            },
+            InputSocket {
+                class: string_class(),
+                default: Some("main".into()),
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Counts calls of a subroutine on the execution stack and branches to 1 when there are more of
+/// them than the limit. `None` subroutine means the guard is not set up and always passes.
+#[derive(Debug, Clone, Default)]
+pub struct RecursionGuardNode {
+    subroutine_start: Option<AbsoluteNodeId>,
+    limit: usize,
+}
+
+impl Node for RecursionGuardNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let Some(start) = &self.subroutine_start else {
+            return Ok(0);
+        };
+        let calls = context
+            .stack_trace()
+            .iter()
+            .skip(1)
+            .filter_map(|id| context.get_node(id))
+            .filter(|node| node.class() == subroutine_class())
+            .filter_map(|node| node.current_variant().parse::<Subroutine>().ok())
+            .filter(|subroutine| subroutine.start() == start)
+            .count();
+        Ok((calls > self.limit) as usize)
+    }
+
+    fn class(&self) -> Class {
+        recursion_guard_class()
+    }
+
+    /// Format: recursion_guard:<subroutine_start_node_id>:<limit>
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: recursion_guard:<subroutine_start_node_id>:<limit>
+    fn current_variant(&self) -> Cow<'_, str> {
+        match &self.subroutine_start {
+            Some(start) => format!("recursion_guard:{start}:{}", self.limit).into(),
+            None => "recursion_guard".into(),
+        }
+    }
+
+    /// Format: recursion_guard:<subroutine_start_node_id>:<limit>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    /// Format: recursion_guard:<subroutine_start_node_id>:<limit>
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        if let Some(params) = variant.strip_prefix("recursion_guard:") {
+            let (start, limit) = params
+                .rsplit_once(':')
+                .ok_or_else(|| VariantError::new(variant, "expected a limit"))?;
+            self.subroutine_start = Some(start.parse().map_err(|e| VariantError::new(variant, e))?);
+            self.limit = limit.parse().map_err(|e| VariantError::new(variant, e))?;
+        } else {
+            *self = Self::default()
+        }
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Describe an object with its class, like `number(42.0)`. Items of arrays and keys and values of
+/// dicts are described the same way.
+fn inspect(value: &Rc<dyn Object>) -> String {
+    let class = value.class();
+    if class == array_class() {
+        let items: Vec<String> = array_items(value).iter().map(inspect).collect();
+        format!("array[{}]", items.join(", "))
+    } else if class == dict_class() {
+        let entries: Vec<String> = dict_entries(value)
+            .iter()
+            .map(|(key, entry)| format!("{}: {}", inspect(key), inspect(entry)))
+            .collect();
+        format!("dict{{{}}}", entries.join(", "))
+    } else {
+        format!("{}({:?})", class.name, value)
+    }
+}
+
+/// Outputs a description of the input with class names, for debugging
+#[derive(Debug, Clone)]
+pub struct InspectNode;
+
+impl Node for InspectNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let res = inspect(&context.get_object(0)?);
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        inspect_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["inspect".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "inspect".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Outputs a stable hash of the input, see `Object::object_hash`. The `hex` variant outputs all 64
+/// bits as a hex string, the `number` variant outputs the upper 53 bits, which a number holds
+/// exactly.
+#[derive(Debug, Clone)]
+pub struct HashNode {
+    as_number: bool,
+}
+
+impl Node for HashNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let hash = context.get_object(0)?.object_hash();
+        let res = if self.as_number {
+            Rc::new((hash >> 11) as f64) as Rc<dyn Object>
+        } else {
+            Rc::new(format!("{hash:016x}")) as Rc<dyn Object>
+        };
+        context.set_outputs(vec![res]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        hash_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["hex".into(), "number".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        if self.as_number { "number" } else { "hex" }.into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.as_number = match variant {
+            "hex" => false,
+            "number" => true,
+            _ => return Err(VariantError::new(variant, "expected `hex` or `number`")),
+        };
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: if self.as_number {
+                number_class()
+            } else {
+                string_class()
+            },
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{run_node, TestProgram};
+
+    /// Main program that reports something before, inside and after a call of a subroutine
+    fn around_call(class: &str) -> TestProgram {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, class, class)
+            .node(2, "subroutine", "subroutine:__main__@20:__main__@22")
+            .node(3, class, class)
+            .end(9, 3)
+            .chain(&[0, 1, 2, 3, 9])
+            .connect(1, 0, 9, 0)
+            .connect(2, 0, 9, 1)
+            .connect(3, 0, 9, 2)
+            .node(20, "start", "start#sub#[]")
+            .node(21, class, class)
+            .end(22, 1)
+            .chain(&[20, 21, 22])
+            .connect(21, 0, 22, 0);
+        program
+    }
+
+    #[test]
+    fn stack_depth_grows_inside_subroutine() {
+        assert_eq!(around_call("stack_depth").run_strings(), ["1", "2", "1"]);
+    }
+
+    #[test]
+    fn caller_is_the_calling_node() {
+        assert_eq!(
+            around_call("caller").run_strings(),
+            ["null", "__main__@2", "null"]
+        );
+    }
+
+    #[test]
+    fn recursion_guard_trips_at_limit() {
+        // Subroutine that calls itself until the guard allows no more than 3 nested calls, then
+        // returns the stack depth
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "subroutine", "subroutine:__main__@20:__main__@24")
+            .end(9, 1)
+            .chain(&[0, 1, 9])
+            .connect(1, 0, 9, 0)
+            .node(20, "start", "start#sub#[]")
+            .node(21, "recursion_guard", "recursion_guard:__main__@20:3")
+            .node(22, "subroutine", "subroutine:__main__@20:__main__@24")
+            .node(23, "stack_depth", "stack_depth")
+            .end(24, 1)
+            .chain(&[20, 21, 22, 24])
+            .edge(21, 1, 23)
+            .edge(23, 0, 24)
+            .connect(22, 0, 24, 0)
+            .connect(23, 0, 24, 0);
+        assert_eq!(program.run_strings(), ["5"]);
+    }
+
+    #[test]
+    fn current_program_and_node_id() {
+        assert_eq!(
+            around_call("current_program").run_strings(),
+            ["__main__", "__main__", "__main__"]
+        );
+        assert_eq!(
+            around_call("current_node_id").run_strings(),
+            ["__main__@1", "__main__@21", "__main__@3"]
+        );
+    }
+
+    fn inspect(class: &str, text: &str) -> String {
+        run_node("inspect", "inspect", &[(class, text)], 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn inspect_values() {
+        assert_eq!(inspect("number", "42"), "number(42.0)");
+        assert_eq!(inspect("string", "hi"), r#"string("hi")"#);
+        assert_eq!(
+            inspect("array", r#"[1, ["a"]]"#),
+            r#"array[number(1.0), array[string("a")]]"#
+        );
+        assert_eq!(
+            inspect("dict", r#"{"keys": 1}"#),
+            r#"dict{string("keys"): number(1.0)}"#
+        );
+    }
+
+    fn hash(class: &str, text: &str) -> String {
+        run_node("hash", "hex", &[(class, text)], 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn equal_objects_hash_equally() {
+        assert_eq!(hash("array", r#"[1, "a"]"#), hash("array", r#"[1.0, "a"]"#));
+        assert_eq!(hash("number", "0"), hash("number", "-0"));
+        assert_eq!(hash("array", "[[0]]"), hash("array", "[[-0.0]]"));
+        assert_eq!(hash("dict", r#"{"a": 0}"#), hash("dict", r#"{"a": -0}"#));
+        assert_eq!(hash("number", "1").len(), 16);
+    }
+
+    #[test]
+    fn different_objects_hash_differently() {
+        assert_ne!(hash("number", "1"), hash("number", "2"));
+        assert_ne!(hash("number", "1"), hash("string", "1"));
+        assert_ne!(hash("array", "[1, 2]"), hash("array", "[2, 1]"));
+    }
+
+    #[test]
+    fn hash_as_number() {
+        let hash = run_node("hash", "number", &[("number", "1")], 1).unwrap();
+        assert!(hash[0].parse::<f64>().unwrap() < 2f64.powi(53));
+    }
+
+    #[test]
+    fn variant_of_print_node() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "node_variant", "node_variant")
+            .const_input(1, 0, "__main__@5")
+            .end(9, 1)
+            .chain(&[0, 1, 9])
+            .connect(1, 0, 9, 0)
+            .node(5, "print", "print:2");
+        assert_eq!(program.run_strings(), ["print:2"]);
+    }
+
+    #[test]
+    fn variant_of_missing_node() {
+        for id in ["__main__@99", "__main__"] {
+            let id = [("string", id)];
+            assert_eq!(run_node("node_variant", "node_variant", &id, 1), None);
+        }
+    }
+
+    fn start_node_of(program: &str, name: &str) -> Option<String> {
+        let inputs = [("string", program), ("string", name)];
+        Some(run_node("start_node_of", "start_node_of", &inputs, 1)?[0].clone())
+    }
+
+    #[test]
+    fn start_node_of_main() {
+        assert_eq!(start_node_of("__main__", "main").unwrap(), "__main__@0");
+    }
+
+    #[test]
+    fn start_node_of_missing_entry() {
+        assert_eq!(start_node_of("__main__", "setup"), None);
+        assert_eq!(start_node_of("elsewhere", "main"), None);
+    }
+}
@@ -0,0 +1,493 @@
+use super::{any_class, array_items, bool_class, cached_class, ron_text, Array, DictVal, NullType};
+use crate::{
+    class::Class,
+    node::{Node, VariantError},
+    object::{
+        class_hash, Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd,
+    },
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, collections::BTreeSet, fmt::Display, rc::Rc, str::FromStr};
+use thiserror::Error;
+
+pub fn set_class() -> Class {
+    cached_class(|| Class {
+        name: "set".into(),
+        nodes: vec![Rc::new(SetConstructor(1)) as Rc<dyn Node>],
+        obj_from_str: Some(<Set as ObjectFromStr>::from_str),
+    })
+}
+
+pub fn set_add_class() -> Class {
+    cached_class(|| Class {
+        name: "set_add".into(),
+        nodes: vec![Rc::new(SetAdd) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn set_contains_class() -> Class {
+    cached_class(|| Class {
+        name: "set_contains".into(),
+        nodes: vec![Rc::new(SetContains) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn set_operation_class() -> Class {
+    cached_class(|| Class {
+        name: "set_operation".into(),
+        nodes: vec![Rc::new(SetOperationNode(SetOperation::Union)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Unordered collection of unique objects. Items are kept sorted the same way as dict keys.
+#[derive(Debug, Clone, Default)]
+pub struct Set(BTreeSet<DictVal>);
+
+/// Collect items of a set object through its fields, in ascending order
+fn set_items(set: &Rc<dyn Object>) -> BTreeSet<DictVal> {
+    array_items(&set.get_field(Rc::new("items".to_string()) as Rc<dyn Object>))
+        .into_iter()
+        .map(DictVal)
+        .collect()
+}
+
+impl FromIterator<Rc<dyn Object>> for Set {
+    fn from_iter<T: IntoIterator<Item = Rc<dyn Object>>>(iter: T) -> Self {
+        Self(iter.into_iter().map(DictVal).collect())
+    }
+}
+
+/// Format: RON sequence of items, `[1, "a"]`. Duplicates are merged.
+impl FromStr for Set {
+    type Err = SetParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let items = ron::from_str::<Vec<ron::Value>>(s)?;
+        Ok(Self(items.iter().map(DictVal::from_ron).collect()))
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum SetParseError {
+    #[error("{0}")]
+    DeserializingError(ron::error::SpannedError),
+}
+
+impl From<ron::error::SpannedError> for SetParseError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        Self::DeserializingError(value)
+    }
+}
+
+/// Same format as `FromStr`, so strings are quoted
+impl Display for Set {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{}]",
+            self.0
+                .iter()
+                .map(|item| ron_text(item.as_ref()))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    }
+}
+
+impl Object for Set {
+    fn class(&self) -> Class {
+        set_class()
+    }
+
+    fn as_number(&self) -> f64 {
+        f64::NAN
+    }
+
+    fn as_bool(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    fn get_field(&self, field: Rc<dyn Object>) -> Rc<dyn Object> {
+        match field.as_string().as_str() {
+            "items" => Rc::new(Array(self.0.iter().map(|v| Rc::clone(v)).collect())),
+            "len" => Rc::new(self.0.len() as f64),
+            _ => Rc::new(NullType),
+        }
+    }
+
+    fn clone_object(&self) -> Rc<dyn Object> {
+        Rc::new(self.clone())
+    }
+
+    /// Hashes of the items are combined, so sets of equal items hash the same
+    fn object_hash(&self) -> u64 {
+        let items = self
+            .0
+            .iter()
+            .flat_map(|item| item.object_hash().to_le_bytes());
+        class_hash("set", items)
+    }
+}
+
+impl ObjectPartialEq for Set {
+    fn eq(&self, other: Rc<dyn Object>) -> bool {
+        other.class() == self.class() && self.0 == set_items(&other)
+    }
+}
+
+impl ObjectPartialOrd for Set {
+    fn partial_cmp(&self, other: Rc<dyn Object>) -> Option<std::cmp::Ordering> {
+        if other.class() == self.class() {
+            Some(Ord::cmp(&self.0, &set_items(&other)))
+        } else {
+            None
+        }
+    }
+}
+
+impl ObjectEq for Set {}
+
+impl ObjectOrd for Set {
+    fn cmp(&self, other: Rc<dyn Object>) -> std::cmp::Ordering {
+        ObjectPartialOrd::partial_cmp(self, other).unwrap()
+    }
+}
+
+/// Make a set of its inputs, the amount of which is set by the variant
+#[derive(Debug, Clone)]
+pub struct SetConstructor(usize);
+
+impl Node for SetConstructor {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let set = Set::from_iter(context.get_inputs()?);
+        context.set_outputs(vec![Rc::new(set) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        set_class()
+    }
+
+    /// Format: set-<amount>
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: set-<amount>
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("set-{}", self.0).into()
+    }
+
+    /// Format: set-<amount>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant
+            .strip_prefix("set-")
+            .ok_or_else(|| VariantError::new(variant, "expected `set-` prefix"))?
+            .parse()
+            .map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None
+            };
+            self.0
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: set_class() }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Outputs a copy of the set with the item added
+#[derive(Debug, Clone)]
+pub struct SetAdd;
+
+impl Node for SetAdd {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let mut items = set_items(&context.get_object(0)?);
+        items.insert(DictVal(context.get_object(1)?));
+        context.set_outputs(vec![Rc::new(Set(items)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        set_add_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["set_add".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "set_add".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: set_class(),
+                default: None,
+            },
+            InputSocket {
+                class: any_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: set_class() }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Whether the set has the item
+#[derive(Debug, Clone)]
+pub struct SetContains;
+
+impl Node for SetContains {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let items = set_items(&context.get_object(0)?);
+        let res = items.contains(&DictVal(context.get_object(1)?));
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        set_contains_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["set_contains".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "set_contains".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: set_class(),
+                default: None,
+            },
+            InputSocket {
+                class: any_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: bool_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SetOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl SetOperation {
+    const ALL: [Self; 3] = [Self::Union, Self::Intersection, Self::Difference];
+}
+
+impl Display for SetOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Union => "union",
+                Self::Intersection => "intersection",
+                Self::Difference => "difference",
+            }
+        )
+    }
+}
+
+impl FromStr for SetOperation {
+    type Err = SetOperationParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "union" => Ok(Self::Union),
+            "intersection" => Ok(Self::Intersection),
+            "difference" => Ok(Self::Difference),
+            s => Err(SetOperationParseError::InvalidVariant(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum SetOperationParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+/// Combine two sets. `union` has items of either set, `intersection` has items of both,
+/// `difference` has items of the first set that are not in the second.
+#[derive(Debug, Clone)]
+pub struct SetOperationNode(SetOperation);
+
+impl Node for SetOperationNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let lhs = set_items(&context.get_object(0)?);
+        let rhs = set_items(&context.get_object(1)?);
+        let res = match self.0 {
+            SetOperation::Union => &lhs | &rhs,
+            SetOperation::Intersection => &lhs & &rhs,
+            SetOperation::Difference => &lhs - &rhs,
+        };
+        context.set_outputs(vec![Rc::new(Set(res)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        set_operation_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        SetOperation::ALL
+            .iter()
+            .map(|v| v.to_string().into())
+            .collect()
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: set_class(),
+                default: None,
+            };
+            2
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: set_class() }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Set;
+    use crate::{
+        object::{Object, ObjectPartialEq},
+        test_utils::run_node,
+    };
+    use std::rc::Rc;
+
+    fn operation(variant: &str, lhs: &str, rhs: &str) -> String {
+        let sets = [("set", lhs), ("set", rhs)];
+        run_node("set_operation", variant, &sets, 1).unwrap()[0].clone()
+    }
+
+    fn contains(set: &str, item: (&str, &str)) -> String {
+        let inputs = [("set", set), item];
+        run_node("set_contains", "set_contains", &inputs, 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn add_and_contains() {
+        let added = run_node(
+            "set_add",
+            "set_add",
+            &[("set", r#"[1, "a"]"#), ("number", "2")],
+            1,
+        )
+        .unwrap();
+        assert_eq!(added, [r#"[1, 2, "a"]"#]);
+        assert_eq!(contains(&added[0], ("number", "2")), "true");
+        assert_eq!(contains(&added[0], ("string", "a")), "true");
+        assert_eq!(contains(&added[0], ("string", "2")), "false");
+    }
+
+    #[test]
+    fn set_operations() {
+        let (lhs, rhs) = ("[1, 2, 3]", "[2, 3, 4]");
+        assert_eq!(operation("union", lhs, rhs), "[1, 2, 3, 4]");
+        assert_eq!(operation("intersection", lhs, rhs), "[2, 3]");
+        assert_eq!(operation("difference", lhs, rhs), "[1]");
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let set: Set = r#"["b, c", "\"d\"", 1, "b, c"]"#.parse().unwrap();
+        let text = set.to_string();
+        assert_eq!(text, r#"[1, "\"d\"", "b, c"]"#);
+        let parsed: Set = text.parse().unwrap();
+        assert!(parsed.eq(Rc::new(set)));
+    }
+
+    #[test]
+    fn set_of_arrays() {
+        let set: Set = r#"[[1, 2], [1], ["a"], [1]]"#.parse().unwrap();
+        assert_eq!(set.to_string(), r#"[[1], [1, 2], ["a"]]"#);
+    }
+
+    #[test]
+    fn fields_and_conversions() {
+        let set: Set = "[1, 2]".parse().unwrap();
+        let field = |name: &str| set.get_field(Rc::new(name.to_string())).to_string();
+        assert_eq!(field("len"), "2");
+        assert_eq!(field("items"), "[1, 2]");
+        assert_eq!(field("size"), "null");
+        assert!(set.as_number().is_nan());
+        assert!(set.as_bool());
+        assert!(!Set::default().as_bool());
+    }
+}
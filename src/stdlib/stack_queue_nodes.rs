@@ -0,0 +1,302 @@
+use super::{any_class, array_items, cached_class, string_class, Array};
+use crate::{
+    class::Class,
+    node::Node,
+    object::Object,
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, rc::Rc};
+
+pub fn stack_push_class() -> Class {
+    cached_class(|| Class {
+        name: "stack_push".into(),
+        nodes: vec![Rc::new(StackPushNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn stack_pop_class() -> Class {
+    cached_class(|| Class {
+        name: "stack_pop".into(),
+        nodes: vec![Rc::new(StackPopNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn queue_enqueue_class() -> Class {
+    cached_class(|| Class {
+        name: "queue_enqueue".into(),
+        nodes: vec![Rc::new(QueueEnqueueNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn queue_dequeue_class() -> Class {
+    cached_class(|| Class {
+        name: "queue_dequeue".into(),
+        nodes: vec![Rc::new(QueueDequeueNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Items of an array stored in a variable, empty if the variable is not set
+fn variable_items(context: &ExecutionContext, name: &str) -> Vec<Rc<dyn Object>> {
+    context
+        .get_variable(name)
+        .map(|array| array_items(&array))
+        .unwrap_or_default()
+}
+
+/// Push a value on top of a stack stored in a variable. Missing variable is an empty stack.
+#[derive(Debug, Clone)]
+pub struct StackPushNode;
+
+impl Node for StackPushNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let name = context.get_string(0)?;
+        let item = context.get_object(1)?;
+        let mut items = variable_items(context, &name);
+        items.push(item);
+        context.set_variable(&name, Rc::new(Array(items)));
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        stack_push_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["push".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "push".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: None,
+            },
+            InputSocket {
+                class: any_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Take the value from the top of a stack stored in a variable. Branches to 1 if the stack is
+/// empty.
+#[derive(Debug, Clone)]
+pub struct StackPopNode;
+
+impl Node for StackPopNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let name = context.get_string(0)?;
+        let mut items = variable_items(context, &name);
+        let Some(item) = items.pop() else {
+            return Ok(1);
+        };
+        context.set_variable(&name, Rc::new(Array(items)));
+        context.set_outputs(vec![item]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        stack_pop_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["pop".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "pop".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: string_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Add a value to the back of a queue stored in a variable. Missing variable is an empty queue.
+#[derive(Debug, Clone)]
+pub struct QueueEnqueueNode;
+
+impl Node for QueueEnqueueNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let name = context.get_string(0)?;
+        let item = context.get_object(1)?;
+        let mut items = variable_items(context, &name);
+        items.push(item);
+        context.set_variable(&name, Rc::new(Array(items)));
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        queue_enqueue_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["enqueue".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "enqueue".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: None,
+            },
+            InputSocket {
+                class: any_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Take the value from the front of a queue stored in a variable. Branches to 1 if the queue
+/// is empty.
+#[derive(Debug, Clone)]
+pub struct QueueDequeueNode;
+
+impl Node for QueueDequeueNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let name = context.get_string(0)?;
+        let mut items = variable_items(context, &name);
+        if items.is_empty() {
+            return Ok(1);
+        }
+        let item = items.remove(0);
+        context.set_variable(&name, Rc::new(Array(items)));
+        context.set_outputs(vec![item]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        queue_dequeue_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["dequeue".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "dequeue".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: string_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{run_node, TestProgram};
+
+    /// Add 1, 2 and 3 to the structure in variable `s`, then take two items out of it
+    fn add_then_take(class_add: &str, add: &str, class_take: &str, take: &str) -> Vec<String> {
+        let mut program = TestProgram::new();
+        program.start(0);
+        for id in 1..=3 {
+            program
+                .node(id, class_add, add)
+                .const_input(id, 0, "s")
+                .const_input(id, 1, &id.to_string());
+        }
+        for id in 11..=12 {
+            program
+                .node(id, class_take, take)
+                .const_input(id, 0, "s")
+                .connect(id, 0, 30, id as usize - 11);
+        }
+        program.end(30, 2).chain(&[0, 1, 2, 3, 11, 12, 30]);
+        program.run_strings()
+    }
+
+    #[test]
+    fn stack_is_lifo() {
+        assert_eq!(
+            add_then_take("stack_push", "push", "stack_pop", "pop"),
+            ["3", "2"]
+        );
+    }
+
+    #[test]
+    fn queue_is_fifo() {
+        assert_eq!(
+            add_then_take("queue_enqueue", "enqueue", "queue_dequeue", "dequeue"),
+            ["1", "2"]
+        );
+    }
+
+    #[test]
+    fn taking_from_empty_is_an_error() {
+        assert_eq!(run_node("stack_pop", "pop", &[("string", "s")], 1), None);
+        assert_eq!(
+            run_node("queue_dequeue", "dequeue", &[("string", "s")], 1),
+            None
+        );
+    }
+}
@@ -0,0 +1,1278 @@
+#[cfg(feature = "regex")]
+use super::NullType;
+use super::{
+    any_class, array_class, array_items, bool_class, cached_class, number_class, string_class,
+    Array,
+};
+use crate::{
+    class::Class,
+    node::{Node, VariantError},
+    object::Object,
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, fmt::Display, rc::Rc, str::FromStr};
+use thiserror::Error;
+
+pub fn join_class() -> Class {
+    cached_class(|| Class {
+        name: "join".into(),
+        nodes: vec![Rc::new(JoinNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn string_search_class() -> Class {
+    cached_class(|| Class {
+        name: "string_search".into(),
+        nodes: vec![Rc::new(StringSearch(StringSearchVariant::Contains)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn string_extract_class() -> Class {
+    cached_class(|| Class {
+        name: "string_extract".into(),
+        nodes: vec![Rc::new(StringExtract(StringExtractVariant {
+            kind: StringExtractKind::Before,
+            strict: false,
+        })) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn string_slice_class() -> Class {
+    cached_class(|| Class {
+        name: "string_slice".into(),
+        nodes: vec![Rc::new(StringSliceNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn string_length_class() -> Class {
+    cached_class(|| Class {
+        name: "string_length".into(),
+        nodes: vec![Rc::new(StringLengthNode(StringLengthUnit::Chars)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn template_class() -> Class {
+    cached_class(|| Class {
+        name: "template".into(),
+        nodes: vec![Rc::new(TemplateNode {
+            strict: false,
+            template: String::new(),
+        }) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn repeat_class() -> Class {
+    cached_class(|| Class {
+        name: "repeat".into(),
+        nodes: vec![Rc::new(RepeatNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+#[cfg(feature = "regex")]
+pub fn regex_class() -> Class {
+    cached_class(|| Class {
+        name: "regex".into(),
+        nodes: vec![Rc::new(RegexNode(RegexVariant::IsMatch)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+#[cfg(feature = "base64")]
+pub fn base64_class() -> Class {
+    cached_class(|| Class {
+        name: "base64".into(),
+        nodes: vec![Rc::new(Base64Node(Base64Variant::Encode)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Join string representations of array items with a separator
+#[derive(Debug, Clone)]
+pub struct JoinNode;
+
+impl Node for JoinNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let separator = context.get_string(1)?;
+        let res = array_items(&context.get_object(0)?)
+            .iter()
+            .map(|item| item.as_string())
+            .collect::<Vec<String>>()
+            .join(&separator);
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        join_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["join".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "join".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: array_class(),
+                default: None,
+            },
+            InputSocket {
+                class: string_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringSearchVariant {
+    Contains,
+    StartsWith,
+    EndsWith,
+    IndexOf,
+}
+
+impl StringSearchVariant {
+    const ALL: [Self; 4] = [
+        Self::Contains,
+        Self::StartsWith,
+        Self::EndsWith,
+        Self::IndexOf,
+    ];
+}
+
+impl Display for StringSearchVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Contains => "contains",
+                Self::StartsWith => "starts-with",
+                Self::EndsWith => "ends-with",
+                Self::IndexOf => "index-of",
+            }
+        )
+    }
+}
+
+impl FromStr for StringSearchVariant {
+    type Err = StringSearchVariantParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "contains" => Ok(Self::Contains),
+            "starts-with" => Ok(Self::StartsWith),
+            "ends-with" => Ok(Self::EndsWith),
+            "index-of" => Ok(Self::IndexOf),
+            s => Err(StringSearchVariantParseError::InvalidVariant(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum StringSearchVariantParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+/// Search for a needle string in a haystack string. `index-of` outputs the index of the first
+/// character of the first match, counted in characters, or -1 if there is no match.
+#[derive(Debug, Clone)]
+pub struct StringSearch(StringSearchVariant);
+
+impl Node for StringSearch {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (haystack, needle) = (context.get_string(0)?, context.get_string(1)?);
+        let res = match self.0 {
+            StringSearchVariant::Contains => Rc::new(haystack.contains(&needle)) as Rc<dyn Object>,
+            StringSearchVariant::StartsWith => Rc::new(haystack.starts_with(&needle)),
+            StringSearchVariant::EndsWith => Rc::new(haystack.ends_with(&needle)),
+            StringSearchVariant::IndexOf => {
+                let index = haystack.find(&needle).map_or(-1.0, |byte_index| {
+                    haystack[..byte_index].chars().count() as f64
+                });
+                Rc::new(index)
+            }
+        };
+        context.set_outputs(vec![res]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        string_search_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        StringSearchVariant::ALL
+            .iter()
+            .map(|v| v.to_string().into())
+            .collect()
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: None,
+            },
+            InputSocket {
+                class: string_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: match self.0 {
+                StringSearchVariant::IndexOf => number_class(),
+                _ => bool_class(),
+            },
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringExtractKind {
+    Before,
+    After,
+    Between,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StringExtractVariant {
+    kind: StringExtractKind,
+    /// Branch to 1 instead of outputting an empty string when a delimiter is missing
+    strict: bool,
+}
+
+impl StringExtractVariant {
+    const KINDS: [StringExtractKind; 3] = [
+        StringExtractKind::Before,
+        StringExtractKind::After,
+        StringExtractKind::Between,
+    ];
+}
+
+impl Display for StringExtractVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            match self.kind {
+                StringExtractKind::Before => "before",
+                StringExtractKind::After => "after",
+                StringExtractKind::Between => "between",
+            },
+            if self.strict { "-strict" } else { "" }
+        )
+    }
+}
+
+impl FromStr for StringExtractVariant {
+    type Err = StringExtractVariantParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, strict) = match s.strip_suffix("-strict") {
+            Some(kind) => (kind, true),
+            None => (s, false),
+        };
+        let kind = match kind {
+            "before" => StringExtractKind::Before,
+            "after" => StringExtractKind::After,
+            "between" => StringExtractKind::Between,
+            _ => return Err(StringExtractVariantParseError::InvalidVariant(s.into())),
+        };
+        Ok(Self { kind, strict })
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum StringExtractVariantParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+/// Part of a string before or after the first occurrence of a delimiter, or between the first
+/// occurrence of a start delimiter and the next occurrence of an end delimiter. A missing
+/// delimiter gives an empty string, or branches to 1 in `-strict` variants.
+#[derive(Debug, Clone)]
+pub struct StringExtract(StringExtractVariant);
+
+impl Node for StringExtract {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (source, delimiter) = (context.get_string(0)?, context.get_string(1)?);
+        let extracted = match self.0.kind {
+            StringExtractKind::Before => source.split_once(&delimiter).map(|(before, _)| before),
+            StringExtractKind::After => source.split_once(&delimiter).map(|(_, after)| after),
+            StringExtractKind::Between => {
+                let end_delimiter = context.get_string(2)?;
+                source
+                    .split_once(&delimiter)
+                    .and_then(|(_, rest)| rest.split_once(&end_delimiter))
+                    .map(|(between, _)| between)
+            }
+        };
+        let res = match extracted {
+            Some(res) => res.to_string(),
+            None if self.0.strict => return Ok(1),
+            None => String::new(),
+        };
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        string_extract_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        [false, true]
+            .into_iter()
+            .flat_map(|strict| {
+                StringExtractVariant::KINDS
+                    .into_iter()
+                    .map(move |kind| StringExtractVariant { kind, strict })
+            })
+            .map(|v| v.to_string().into())
+            .collect()
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        let amount = match self.0.kind {
+            StringExtractKind::Between => 3,
+            _ => 2,
+        };
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: None,
+            };
+            amount
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Slice a string by character positions, not bytes. Negative positions count from the end of the
+/// string. Positions are clamped to the string's length, an empty string is output if start is not
+/// before end. End defaults to the end of the string.
+#[derive(Debug, Clone)]
+pub struct StringSliceNode;
+
+impl Node for StringSliceNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let string = context.get_string(0)?;
+        let len = string.chars().count();
+        let resolve = |pos: f64| {
+            let pos = pos.trunc();
+            let pos = if pos < 0.0 { len as f64 + pos } else { pos };
+            pos.clamp(0.0, len as f64) as usize
+        };
+        let start = resolve(context.get_number(1)?);
+        let end = resolve(context.get_number(2)?);
+        let res = string
+            .chars()
+            .skip(start)
+            .take(end.saturating_sub(start))
+            .collect::<String>();
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        string_slice_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["string_slice".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "string_slice".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: None,
+            },
+            InputSocket {
+                class: number_class(),
+                default: Some("0".into()),
+            },
+            InputSocket {
+                class: number_class(),
+                default: Some("inf".into()),
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StringLengthUnit {
+    Bytes,
+    Chars,
+    #[cfg(feature = "unicode-segmentation")]
+    Graphemes,
+}
+
+impl StringLengthUnit {
+    const ALL: &'static [Self] = &[
+        Self::Bytes,
+        Self::Chars,
+        #[cfg(feature = "unicode-segmentation")]
+        Self::Graphemes,
+    ];
+}
+
+impl Display for StringLengthUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Bytes => "bytes",
+                Self::Chars => "chars",
+                #[cfg(feature = "unicode-segmentation")]
+                Self::Graphemes => "graphemes",
+            }
+        )
+    }
+}
+
+impl FromStr for StringLengthUnit {
+    type Err = StringLengthUnitParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bytes" => Ok(Self::Bytes),
+            "chars" => Ok(Self::Chars),
+            #[cfg(feature = "unicode-segmentation")]
+            "graphemes" => Ok(Self::Graphemes),
+            s => Err(StringLengthUnitParseError::InvalidVariant(s.into())),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+enum StringLengthUnitParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+/// Length of a string in UTF-8 bytes, chars (unicode scalar values) or, with the
+/// `unicode-segmentation` feature, graphemes (user-perceived characters)
+#[derive(Debug, Clone)]
+pub struct StringLengthNode(StringLengthUnit);
+
+impl Node for StringLengthNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let string = context.get_string(0)?;
+        let len = match self.0 {
+            StringLengthUnit::Bytes => string.len(),
+            StringLengthUnit::Chars => string.chars().count(),
+            #[cfg(feature = "unicode-segmentation")]
+            StringLengthUnit::Graphemes => {
+                use unicode_segmentation::UnicodeSegmentation;
+
+                string.graphemes(true).count()
+            }
+        };
+        context.set_outputs(vec![Rc::new(len as f64) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        string_length_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        StringLengthUnit::ALL
+            .iter()
+            .map(|v| v.to_string().into())
+            .collect()
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: string_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Fill `${name}` references in a template with string forms of variables. Missing variables are
+/// left empty, or with the `template-strict` variants, make the node branch to 1. A `${` without a
+/// closing `}` is kept as is.
+#[derive(Debug, Clone)]
+pub struct TemplateNode {
+    strict: bool,
+    template: String,
+}
+
+impl Node for TemplateNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let mut res = String::new();
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find("${") {
+            let Some(len) = rest[start + 2..].find('}') else {
+                break;
+            };
+            res.push_str(&rest[..start]);
+            let name = &rest[start + 2..start + 2 + len];
+            match context.get_variable(name) {
+                Some(value) => res.push_str(&value.as_string()),
+                None if self.strict => return Ok(1),
+                None => {}
+            }
+            rest = &rest[start + 3 + len..];
+        }
+        res.push_str(rest);
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        template_class()
+    }
+
+    /// Format: template:<template> or template-strict:<template>
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: template:<template> or template-strict:<template>
+    fn current_variant(&self) -> Cow<'_, str> {
+        if self.strict {
+            format!("template-strict:{}", self.template).into()
+        } else {
+            format!("template:{}", self.template).into()
+        }
+    }
+
+    /// Format: template:<template> or template-strict:<template>
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        let (strict, template) = if let Some(template) = variant.strip_prefix("template:") {
+            (false, template)
+        } else if let Some(template) = variant.strip_prefix("template-strict:") {
+            (true, template)
+        } else {
+            return Err(VariantError::new(
+                variant,
+                "expected `template:` or `template-strict:` prefix",
+            ));
+        };
+        self.strict = strict;
+        self.template = template.into();
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Most bytes of a repeated string or items of a repeated array
+const MAX_REPEAT_LEN: usize = 10_000_000;
+
+/// Repeat a string or the items of an array the given amount of times. Counts below 1 give an
+/// empty string or array. Branches to 1 for other classes and if the result would be longer than
+/// 10 million bytes or items.
+#[derive(Debug, Clone)]
+pub struct RepeatNode;
+
+impl Node for RepeatNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let value = context.get_object(0)?;
+        let count = context.get_number(1)?.max(0.0) as usize;
+        let repeated_len = |len: usize| {
+            len.checked_mul(count)
+                .filter(|repeated| *repeated <= MAX_REPEAT_LEN)
+        };
+        let res = match value.class().name.as_str() {
+            "string" => {
+                let string = value.as_string();
+                if repeated_len(string.len()).is_none() {
+                    return Ok(1);
+                }
+                Rc::new(string.repeat(count)) as Rc<dyn Object>
+            }
+            "array" => {
+                let items = array_items(&value);
+                let Some(len) = repeated_len(items.len()) else {
+                    return Ok(1);
+                };
+                let res = items.iter().cloned().cycle().take(len).collect();
+                Rc::new(Array(res)) as Rc<dyn Object>
+            }
+            _ => return Ok(1),
+        };
+        context.set_outputs(vec![res]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        repeat_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["repeat".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "repeat".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: any_class(),
+                default: None,
+            },
+            InputSocket {
+                class: number_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RegexVariant {
+    IsMatch,
+    Replace,
+    Captures,
+}
+
+#[cfg(feature = "regex")]
+impl RegexVariant {
+    const ALL: [Self; 3] = [Self::IsMatch, Self::Replace, Self::Captures];
+}
+
+#[cfg(feature = "regex")]
+impl Display for RegexVariant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::IsMatch => "is-match",
+                Self::Replace => "replace",
+                Self::Captures => "captures",
+            }
+        )
+    }
+}
+
+#[cfg(feature = "regex")]
+impl FromStr for RegexVariant {
+    type Err = RegexVariantParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "is-match" => Ok(Self::IsMatch),
+            "replace" => Ok(Self::Replace),
+            "captures" => Ok(Self::Captures),
+            s => Err(RegexVariantParseError::InvalidVariant(s.into())),
+        }
+    }
+}
+
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone, Error)]
+enum RegexVariantParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+/// Match text against a regular expression pattern. `replace` replaces all matches with the
+/// third input, `captures` outputs groups of the first match (null for groups that did not
+/// participate, empty array if nothing matched). Branches to 1 if the pattern is invalid.
+#[cfg(feature = "regex")]
+#[derive(Debug, Clone)]
+pub struct RegexNode(RegexVariant);
+
+#[cfg(feature = "regex")]
+impl Node for RegexNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let text = context.get_string(0)?;
+        let Ok(regex) = regex::Regex::new(&context.get_string(1)?) else {
+            return Ok(1);
+        };
+        let res = match self.0 {
+            RegexVariant::IsMatch => Rc::new(regex.is_match(&text)) as Rc<dyn Object>,
+            RegexVariant::Replace => Rc::new(
+                regex
+                    .replace_all(&text, context.get_string(2)?.as_str())
+                    .into_owned(),
+            ),
+            RegexVariant::Captures => {
+                let groups = regex
+                    .captures(&text)
+                    .map(|captures| {
+                        captures
+                            .iter()
+                            .map(|group| match group {
+                                Some(m) => Rc::new(m.as_str().to_string()) as Rc<dyn Object>,
+                                None => Rc::new(NullType) as Rc<dyn Object>,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Rc::new(Array(groups))
+            }
+        };
+        context.set_outputs(vec![res]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        regex_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        RegexVariant::ALL
+            .iter()
+            .map(|v| v.to_string().into())
+            .collect()
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        let amount = match self.0 {
+            RegexVariant::Replace => 3,
+            _ => 2,
+        };
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: None,
+            };
+            amount
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: match self.0 {
+                RegexVariant::IsMatch => bool_class(),
+                RegexVariant::Replace => string_class(),
+                RegexVariant::Captures => array_class(),
+            },
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(feature = "base64")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Base64Variant {
+    Encode,
+    Decode,
+}
+
+#[cfg(feature = "base64")]
+impl Display for Base64Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Encode => "encode",
+                Self::Decode => "decode",
+            }
+        )
+    }
+}
+
+#[cfg(feature = "base64")]
+impl FromStr for Base64Variant {
+    type Err = Base64VariantParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "encode" => Ok(Self::Encode),
+            "decode" => Ok(Self::Decode),
+            s => Err(Base64VariantParseError::InvalidVariant(s.into())),
+        }
+    }
+}
+
+#[cfg(feature = "base64")]
+#[derive(Debug, Clone, Error)]
+enum Base64VariantParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+}
+
+/// Encode a string as standard padded base64, or decode it back. Branches to 1 if the input of
+/// `decode` is not valid base64 or doesn't decode to UTF-8 text.
+#[cfg(feature = "base64")]
+#[derive(Debug, Clone)]
+pub struct Base64Node(Base64Variant);
+
+#[cfg(feature = "base64")]
+impl Node for Base64Node {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        use base64::{engine::general_purpose::STANDARD, Engine};
+
+        let input = context.get_string(0)?;
+        let res = match self.0 {
+            Base64Variant::Encode => STANDARD.encode(input),
+            Base64Variant::Decode => {
+                let Some(res) = STANDARD
+                    .decode(input)
+                    .ok()
+                    .and_then(|bytes| String::from_utf8(bytes).ok())
+                else {
+                    return Ok(1);
+                };
+                res
+            }
+        };
+        context.set_outputs(vec![Rc::new(res) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        base64_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["encode".into(), "decode".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        self.0.to_string().into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: string_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: string_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{run_node, TestProgram};
+    use std::rc::Rc;
+
+    fn join(array: &str) -> String {
+        run_node("join", "join", &[("array", array), ("string", ", ")], 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn join_with_separator() {
+        assert_eq!(join(r#"["a", "b", "c"]"#), "a, b, c");
+    }
+
+    #[test]
+    fn join_empty_and_single() {
+        assert_eq!(join("[]"), "");
+        assert_eq!(join(r#"["a"]"#), "a");
+    }
+
+    fn search(variant: &str, haystack: &str, needle: &str) -> String {
+        run_node(
+            "string_search",
+            variant,
+            &[("string", haystack), ("string", needle)],
+            1,
+        )
+        .unwrap()[0]
+            .clone()
+    }
+
+    #[test]
+    fn search_variants() {
+        assert_eq!(search("contains", "hello", "ell"), "true");
+        assert_eq!(search("contains", "hello", "xyz"), "false");
+        assert_eq!(search("starts-with", "hello", "he"), "true");
+        assert_eq!(search("ends-with", "hello", "he"), "false");
+        assert_eq!(search("index-of", "hello", "l"), "2");
+        assert_eq!(search("index-of", "hello", "z"), "-1");
+    }
+
+    #[test]
+    fn index_of_in_multibyte_string() {
+        assert_eq!(search("index-of", "héllo wörld", "wö"), "6");
+    }
+
+    #[cfg(feature = "regex")]
+    fn regex(variant: &str, inputs: &[(&str, &str)]) -> Option<String> {
+        Some(run_node("regex", variant, inputs, 1)?[0].clone())
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_match_and_replace() {
+        let text = ("string", "order 66 and 42");
+        let digits = ("string", r"\d+");
+        assert_eq!(regex("is-match", &[text, digits]).unwrap(), "true");
+        assert_eq!(
+            regex("replace", &[text, digits, ("string", "#")]).unwrap(),
+            "order # and #"
+        );
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_captures() {
+        let captures = regex(
+            "captures",
+            &[("string", "key=value"), ("string", r"(\w+)=(\w+)")],
+        );
+        assert_eq!(captures.unwrap(), "[key=value, key, value]");
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn invalid_regex_is_an_error() {
+        assert_eq!(
+            regex("is-match", &[("string", "text"), ("string", "(")]),
+            None
+        );
+    }
+
+    fn extract(variant: &str, delimiters: &[&str]) -> Option<Vec<String>> {
+        let inputs: Vec<(&str, &str)> = [("string", "key=[value]")]
+            .into_iter()
+            .chain(delimiters.iter().map(|delimiter| ("string", *delimiter)))
+            .collect();
+        run_node("string_extract", variant, &inputs, 1)
+    }
+
+    #[test]
+    fn extract_around_delimiters() {
+        assert_eq!(extract("before", &["="]).unwrap(), ["key"]);
+        assert_eq!(extract("after", &["="]).unwrap(), ["[value]"]);
+        assert_eq!(extract("between", &["[", "]"]).unwrap(), ["value"]);
+    }
+
+    #[test]
+    fn missing_delimiter() {
+        assert_eq!(extract("before", &[":"]).unwrap(), [""]);
+        assert_eq!(extract("between", &["[", ")"]).unwrap(), [""]);
+        assert_eq!(extract("after-strict", &[":"]), None);
+        assert_eq!(extract("between-strict", &["(", "]"]), None);
+    }
+
+    #[cfg(feature = "base64")]
+    fn base64(variant: &str, text: &str) -> Option<String> {
+        Some(run_node("base64", variant, &[("string", text)], 1)?[0].clone())
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn base64_round_trip() {
+        for text in ["", "a", "hello, world", "ünïcödé"] {
+            let encoded = base64("encode", text).unwrap();
+            assert_eq!(base64("decode", &encoded).unwrap(), text);
+        }
+        assert_eq!(base64("encode", "hi").unwrap(), "aGk=");
+    }
+
+    #[cfg(feature = "base64")]
+    #[test]
+    fn malformed_base64() {
+        assert_eq!(base64("decode", "not base64!"), None);
+        assert_eq!(base64("decode", "/w=="), None);
+    }
+
+    fn slice(string: &str, start: &str, end: &str) -> String {
+        let inputs = [("string", string), ("number", start), ("number", end)];
+        run_node("string_slice", "string_slice", &inputs, 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn slice_multibyte_with_negative_bounds() {
+        assert_eq!(slice("héllo wörld", "-5", "-1"), "wörl");
+        assert_eq!(slice("héllo wörld", "1", "-6"), "éllo");
+        assert_eq!(slice("日本語テキスト", "-4", "inf"), "テキスト");
+    }
+
+    #[test]
+    fn slice_out_of_range() {
+        assert_eq!(slice("héllo", "-100", "2"), "hé");
+        assert_eq!(slice("héllo", "3", "100"), "lo");
+        assert_eq!(slice("héllo", "10", "20"), "");
+        assert_eq!(slice("héllo", "4", "2"), "");
+    }
+
+    fn repeat(value: (&str, &str), count: &str) -> Option<String> {
+        Some(run_node("repeat", "repeat", &[value, ("number", count)], 1)?[0].clone())
+    }
+
+    #[test]
+    fn repeat_string_and_array() {
+        assert_eq!(repeat(("string", "ab"), "3").unwrap(), "ababab");
+        assert_eq!(repeat(("array", "[1, 2]"), "2").unwrap(), "[1, 2, 1, 2]");
+    }
+
+    #[test]
+    fn repeat_zero_times() {
+        assert_eq!(repeat(("string", "ab"), "0").unwrap(), "");
+        assert_eq!(repeat(("array", "[1, 2]"), "-1").unwrap(), "[]");
+        assert_eq!(repeat(("array", "[]"), "inf").unwrap(), "[]");
+        assert_eq!(repeat(("string", ""), "1e19").unwrap(), "");
+    }
+
+    #[test]
+    fn repeat_too_many_times() {
+        for count in ["1e18", "1e19", "inf"] {
+            assert_eq!(repeat(("string", "ab"), count), None);
+            assert_eq!(repeat(("array", "[1, 2]"), count), None);
+        }
+        assert_eq!(repeat(("string", "ab"), "5000001"), None);
+        assert_eq!(repeat(("number", "1"), "2"), None);
+    }
+
+    /// "café" with a combining accent, then a thumbs up with a skin tone modifier
+    const ACCENTED_EMOJI: &str = "cafe\u{301} \u{1f44d}\u{1f3fd}";
+
+    fn string_length(unit: &str) -> String {
+        let string = [("string", ACCENTED_EMOJI)];
+        run_node("string_length", unit, &string, 1).unwrap()[0].clone()
+    }
+
+    #[test]
+    fn bytes_and_chars() {
+        assert_eq!(string_length("bytes"), "15");
+        assert_eq!(string_length("chars"), "8");
+    }
+
+    #[cfg(feature = "unicode-segmentation")]
+    #[test]
+    fn graphemes() {
+        assert_eq!(string_length("graphemes"), "6");
+    }
+
+    /// Fill a template with `name` set to "Ann" and `count` set to 3. `None` if the node branches
+    /// to 1.
+    fn template(variant: &str) -> Option<String> {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "template", variant)
+            .end(9, 1)
+            .chain(&[0, 1, 9])
+            .connect(1, 0, 9, 0)
+            .end(8, 0)
+            .edge(1, 1, 8);
+        let mut executor = program.executor();
+        executor.set_variable("name", Rc::new("Ann".to_string()));
+        executor.set_variable("count", Rc::new(3.0));
+        executor.start_execution(true).unwrap();
+        let output = executor.final_output().unwrap();
+        output.first().map(|value| value.to_string())
+    }
+
+    #[test]
+    fn template_with_present_variables() {
+        let text = "Hello ${name}, you have ${count}";
+        let expected = "Hello Ann, you have 3";
+        assert_eq!(template(&format!("template:{text}")).unwrap(), expected);
+        assert_eq!(
+            template(&format!("template-strict:{text}")).unwrap(),
+            expected
+        );
+        assert_eq!(
+            template("template:${name} ${unclosed").unwrap(),
+            "Ann ${unclosed"
+        );
+    }
+
+    #[test]
+    fn template_with_missing_variables() {
+        let text = "Hello ${name}, you have ${messages}";
+        let filled = template(&format!("template:{text}"));
+        assert_eq!(filled.unwrap(), "Hello Ann, you have ");
+        assert_eq!(template(&format!("template-strict:{text}")), None);
+    }
+}
@@ -3,18 +3,18 @@ use crate::{
     node::Node,
     object::{Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd},
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
 
-use super::any_class;
+use super::{any_class, cached_class};
 use std::rc::Rc;
 
 pub fn string_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "string".into(),
         nodes: vec![Rc::new(StringNode) as Rc<dyn Node>],
         obj_from_str: Some(<String as ObjectFromStr>::from_str),
-    }
+    })
 }
 
 impl Object for String {
@@ -22,8 +22,9 @@ impl Object for String {
         string_class()
     }
 
+    /// NaN if the string is not a number
     fn as_number(&self) -> f64 {
-        self.parse().unwrap()
+        self.parse().unwrap_or(f64::NAN)
     }
 
     fn as_bool(&self) -> bool {
@@ -63,10 +64,10 @@ impl ObjectOrd for String {
 pub struct StringNode;
 
 impl Node for StringNode {
-    fn execute(&self, context: &mut ExecutionContext) -> usize {
-        let ret = context.get_inputs()[0].as_string();
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let ret = context.get_string(0)?;
         context.set_outputs(vec![Rc::new(ret) as Rc<dyn Object>]);
-        0
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -84,7 +85,10 @@ impl Node for StringNode {
     fn set_variant(&mut self, _variant: &str) {}
 
     fn inputs(&self) -> Vec<InputSocket> {
-        vec![InputSocket { class: any_class() }]
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
     }
 
     fn outputs(&self) -> Vec<OutputSocket> {
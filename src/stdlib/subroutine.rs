@@ -1,24 +1,45 @@
+use super::{any_class, cached_class, error_class, number_class, string_class, ErrorObject};
 use crate::{
     class::Class,
     module::ModulePath,
-    node::{AbsoluteNodeId, Node, NodeId},
+    node::{AbsoluteNodeId, AbsoluteNodeIdParseError, Node, NodeId, VariantError},
+    object::{Object, ObjectEq, ObjectFromStr, ObjectOrd, ObjectPartialEq, ObjectPartialOrd},
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
-use std::{borrow::Cow, rc::Rc, str::FromStr};
+use std::{borrow::Cow, fmt::Display, rc::Rc, str::FromStr};
+use thiserror::Error;
 
 /// The node provided should be cloned and set the proper ids before any use. By default, all ids
 /// are at their max values
 pub fn subroutine_class() -> Class {
-    let empty_path = ModulePath(vec![], String::new());
-    Class {
-        name: "subroutine".into(),
-        nodes: vec![Rc::new(Subroutine(
-            AbsoluteNodeId(empty_path.clone(), NodeId::MAX),
-            AbsoluteNodeId(empty_path, NodeId::MAX),
-        )) as Rc<dyn Node>],
+    cached_class(|| {
+        let empty_path = ModulePath(vec![], String::new());
+        Class {
+            name: "subroutine".into(),
+            nodes: vec![Rc::new(Subroutine(
+                AbsoluteNodeId(empty_path.clone(), NodeId::MAX),
+                AbsoluteNodeId(empty_path, NodeId::MAX),
+            )) as Rc<dyn Node>],
+            obj_from_str: Some(<Subroutine as ObjectFromStr>::from_str),
+        }
+    })
+}
+
+pub fn subroutine_build_class() -> Class {
+    cached_class(|| Class {
+        name: "subroutine_build".into(),
+        nodes: vec![Rc::new(SubroutineBuildNode) as Rc<dyn Node>],
         obj_from_str: None,
-    }
+    })
+}
+
+pub fn retry_class() -> Class {
+    cached_class(|| Class {
+        name: "retry".into(),
+        nodes: vec![Rc::new(RetryNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
 }
 
 /// This is a special class that tells to look to the node id outputs provided in the class for inputs
@@ -44,11 +65,102 @@ pub fn subroutine_output_class(id: &AbsoluteNodeId) -> Class {
 #[derive(Debug, Clone)]
 pub struct Subroutine(AbsoluteNodeId, AbsoluteNodeId);
 
+impl Subroutine {
+    /// Id of the start node of the subroutine
+    pub fn start(&self) -> &AbsoluteNodeId {
+        &self.0
+    }
+
+    /// Id of the end node of the subroutine
+    pub fn end(&self) -> &AbsoluteNodeId {
+        &self.1
+    }
+}
+
+/// Format: subroutine:<start_node_id>:<end_node_id>
+impl FromStr for Subroutine {
+    type Err = SubroutineParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ids = s
+            .strip_prefix("subroutine:")
+            .ok_or_else(|| SubroutineParseError::InvalidVariant(s.into()))?
+            .split(':');
+        let (Some(id_start), Some(id_end)) = (ids.next(), ids.next()) else {
+            return Err(SubroutineParseError::InvalidVariant(s.into()));
+        };
+        Ok(Self(
+            AbsoluteNodeId::from_str(id_start)?,
+            AbsoluteNodeId::from_str(id_end)?,
+        ))
+    }
+}
+
+impl Display for Subroutine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "subroutine:{}:{}", self.0, self.1)
+    }
+}
+
+/// As an object, a subroutine is a reference to the subroutine that can be passed around. Its
+/// string form is the variant of a node calling it.
+impl Object for Subroutine {
+    fn class(&self) -> Class {
+        subroutine_class()
+    }
+
+    fn as_number(&self) -> f64 {
+        f64::NAN
+    }
+
+    fn as_bool(&self) -> bool {
+        true
+    }
+}
+
+impl ObjectPartialEq for Subroutine {
+    fn eq(&self, other: Rc<dyn Object>) -> bool {
+        Object::class(self) == other.class() && self.to_string() == other.as_string()
+    }
+}
+
+impl ObjectPartialOrd for Subroutine {
+    fn partial_cmp(&self, other: Rc<dyn Object>) -> Option<std::cmp::Ordering> {
+        if Object::class(self) == other.class() {
+            PartialOrd::partial_cmp(&self.to_string(), &other.as_string())
+        } else {
+            None
+        }
+    }
+}
+
+impl ObjectEq for Subroutine {}
+
+impl ObjectOrd for Subroutine {
+    fn cmp(&self, other: Rc<dyn Object>) -> std::cmp::Ordering {
+        ObjectPartialOrd::partial_cmp(self, other).unwrap()
+    }
+}
+
+#[derive(Debug, Clone, Error)]
+pub enum SubroutineParseError {
+    #[error("Invalid variant: {0}")]
+    InvalidVariant(String),
+    #[error("Failed to parse node id: {0}")]
+    NodeIdParseError(AbsoluteNodeIdParseError),
+}
+
+impl From<AbsoluteNodeIdParseError> for SubroutineParseError {
+    fn from(e: AbsoluteNodeIdParseError) -> Self {
+        Self::NodeIdParseError(e)
+    }
+}
+
 impl Node for Subroutine {
-    fn execute(&self, context: &mut ExecutionContext) -> usize {
-        let inputs = context.get_inputs();
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let inputs = context.get_inputs()?;
         context.execute_subroutine(self.0.clone(), inputs);
-        0
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -60,6 +172,17 @@ impl Node for Subroutine {
         vec![self.current_variant()]
     }
 
+    fn variant_descriptions(&self) -> Vec<(Cow<'_, str>, Cow<'_, str>)> {
+        vec![(
+            self.current_variant(),
+            format!(
+                "Call subroutine starting at {} and ending at {}",
+                self.0, self.1
+            )
+            .into(),
+        )]
+    }
+
     /// Format: subroutine:<start_node_id>:<end_node_id>
     fn current_variant(&self) -> Cow<'_, str> {
         format!("subroutine:{}:{}", self.0, self.1).into()
@@ -67,16 +190,18 @@ impl Node for Subroutine {
 
     /// Format: subroutine@<start_node_id>:<end_node_id>
     fn set_variant(&mut self, variant: &str) {
-        let mut ids = variant.strip_prefix("subroutine:").unwrap().split(':');
-        let id_start = ids.next().unwrap();
-        let id_end = ids.next().unwrap();
-        self.0 = AbsoluteNodeId::from_str(id_start).unwrap();
-        self.1 = AbsoluteNodeId::from_str(id_end).unwrap()
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        *self = variant.parse().map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
     }
 
     fn inputs(&self) -> Vec<InputSocket> {
         vec![InputSocket {
             class: subroutine_input_class(&self.0),
+            default: None,
         }]
     }
 
@@ -94,3 +219,221 @@ impl Node for Subroutine {
         true
     }
 }
+
+/// Make a subroutine object from ids of its start and end nodes, so that subroutines to call can
+/// be chosen at runtime. Branches to 1 if either id is malformed.
+#[derive(Debug, Clone)]
+pub struct SubroutineBuildNode;
+
+impl Node for SubroutineBuildNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (start, end) = (context.get_string(0)?, context.get_string(1)?);
+        let (Ok(start), Ok(end)) = (start.parse(), end.parse()) else {
+            return Ok(1);
+        };
+        context.set_outputs(vec![Rc::new(Subroutine(start, end)) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        subroutine_build_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["subroutine_build".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "subroutine_build".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: None,
+            };
+            2
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: subroutine_class(),
+        }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Call a subroutine without inputs until it succeeds, at most the given number of times. A call
+/// fails if executing the subroutine fails or if the first value it returns is an error. Outputs
+/// the first value returned by the successful call, or branches to 1 with the last error once all
+/// attempts have failed. At least one attempt is made. Aborting execution is not retried.
+#[derive(Debug, Clone)]
+pub struct RetryNode;
+
+impl Node for RetryNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let subroutine: Subroutine = context.get_object(0)?.to_string().parse().unwrap();
+        let attempts = context.get_number(1)?.max(1.0) as u64;
+        let mut last_error = None;
+        for _ in 0..attempts {
+            match context.try_run_subroutine(subroutine.start().clone(), vec![]) {
+                Ok(values) => match values.into_iter().next() {
+                    Some(value) if value.class() == error_class() => last_error = Some(value),
+                    value => {
+                        context.set_outputs(value.into_iter().collect());
+                        return Ok(0);
+                    }
+                },
+                Err(ExecutionError::Aborted) => return Err(ExecutionError::Aborted),
+                Err(e) => {
+                    last_error = Some(
+                        Rc::new(ErrorObject::new("execution", e.to_string())) as Rc<dyn Object>
+                    )
+                }
+            }
+        }
+        context.set_outputs(last_error.into_iter().collect());
+        Ok(1)
+    }
+
+    fn class(&self) -> Class {
+        retry_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["retry".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "retry".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: subroutine_class(),
+                default: None,
+            },
+            InputSocket {
+                class: number_class(),
+                default: Some("3".into()),
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }]
+    }
+
+    fn branches(&self) -> u32 {
+        2
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{run_node, TestProgram};
+
+    #[test]
+    fn built_subroutine_is_called() {
+        let ids = [("string", "__main__@20"), ("string", "__main__@22")];
+        let built = run_node("subroutine_build", "subroutine_build", &ids, 1).unwrap();
+        assert_eq!(built, ["subroutine:__main__@20:__main__@22"]);
+
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "subroutine", &built[0])
+            .end(9, 1)
+            .chain(&[0, 1, 9])
+            .connect(1, 0, 9, 0)
+            .node(20, "start", "start#sub#[]")
+            .value(21, "string", "called")
+            .end(22, 1)
+            .chain(&[20, 21, 22])
+            .connect(21, 0, 22, 0);
+        assert_eq!(program.run_strings(), ["called"]);
+    }
+
+    #[test]
+    fn malformed_id_is_an_error() {
+        let ids = [("string", "__main__@20"), ("string", "__main__")];
+        assert_eq!(
+            run_node("subroutine_build", "subroutine_build", &ids, 1),
+            None
+        );
+    }
+
+    /// Retry a subroutine that counts its calls in the `tries` variable and returns an error
+    /// until the call with the given number. Returns what the retry node outputs and the amount
+    /// of calls.
+    fn retry(succeed_on: &str, attempts: &str) -> (String, f64) {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "retry", "retry")
+            .const_input(1, 0, "subroutine:__main__@20:__main__@27")
+            .const_input(1, 1, attempts)
+            .end(9, 1)
+            .chain(&[0, 1, 9])
+            .connect(1, 0, 9, 0)
+            .end(8, 1)
+            .edge(1, 1, 8)
+            .connect(1, 0, 8, 0);
+        program
+            .node(20, "start", "start#sub#[]")
+            .node(21, "accumulate", "add")
+            .const_input(21, 0, "tries")
+            .value(22, "number", succeed_on)
+            .node(23, "equals", "eq")
+            .node(24, "if", "if")
+            .chain(&[20, 21, 22, 23, 24])
+            .connect(21, 0, 23, 0)
+            .connect(22, 0, 23, 1)
+            .connect(23, 0, 24, 0)
+            .value(25, "string", "ok")
+            .end(26, 1)
+            .edge(24, 1, 25)
+            .edge(25, 0, 26)
+            .connect(25, 0, 26, 0)
+            .node(27, "error", "new")
+            .const_input(27, 0, "flaky")
+            .const_input(27, 1, "not yet")
+            .end(28, 1)
+            .edge(24, 0, 27)
+            .edge(27, 0, 28)
+            .connect(27, 0, 28, 0);
+        let mut executor = program.executor();
+        executor.start_execution(true).unwrap();
+        let output = executor.final_output().unwrap()[0].to_string();
+        let tries = executor.get_variable("tries").unwrap().as_number();
+        (output, tries)
+    }
+
+    #[test]
+    fn retry_until_success() {
+        assert_eq!(retry("3", "5"), ("ok".into(), 3.0));
+    }
+
+    #[test]
+    fn retry_always_failing() {
+        assert_eq!(retry("0", "4"), ("flaky: not yet".into(), 4.0));
+    }
+}
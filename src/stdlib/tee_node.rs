@@ -0,0 +1,103 @@
+use super::{any_class, cached_class};
+use crate::{
+    class::Class,
+    node::{Node, VariantError},
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, rc::Rc};
+
+pub fn tee_class() -> Class {
+    cached_class(|| Class {
+        name: "tee".into(),
+        nodes: vec![Rc::new(TeeNode(2)) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Pass the input to several outputs, amount of which is set by the variant (`tee-3`)
+#[derive(Debug, Clone)]
+pub struct TeeNode(usize);
+
+impl Node for TeeNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let value = context.get_object(0)?;
+        context.set_outputs(vec![value; self.0]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        tee_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("tee-{}", self.0).into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.0 = variant
+            .strip_prefix("tee-")
+            .ok_or_else(|| VariantError::new(variant, "expected `tee-` prefix"))?
+            .parse()
+            .map_err(|e| VariantError::new(variant, e))?;
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket { class: any_class() }; self.0]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{SharedOutput, TestProgram};
+
+    #[test]
+    fn tee_into_three_prints() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "string", "same")
+            .node(2, "tee", "tee-3")
+            .connect(1, 0, 2, 0)
+            .end(9, 0);
+        let mut order = vec![0, 1, 2];
+        for output in 0..3 {
+            let id = 3 + output as u32;
+            program
+                .node(id, "print", "println")
+                .connect(2, output, id, 0);
+            order.push(id);
+        }
+        order.push(9);
+        program.chain(&order);
+        let mut executor = program.executor();
+        let output = SharedOutput::default();
+        executor.set_output(Box::new(output.clone()));
+        executor.start_execution(true).unwrap();
+        assert_eq!(output.text(), "same\nsame\nsame\n");
+    }
+}
@@ -0,0 +1,118 @@
+use super::{any_class, cached_class};
+use crate::{
+    class::Class,
+    node::{Node, VariantError},
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, rc::Rc};
+
+pub fn type_switch_class() -> Class {
+    cached_class(|| Class {
+        name: "typeswitch".into(),
+        nodes: vec![Rc::new(TypeSwitchNode(vec![])) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Branch on the class of the input. Variant lists class names (`typeswitch:number,string`), the
+/// node branches to the index of the first name matching the input's class, or to the last
+/// branch if none match.
+#[derive(Debug, Clone)]
+pub struct TypeSwitchNode(Vec<String>);
+
+impl Node for TypeSwitchNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let class = context.get_object(0)?.class();
+        Ok(self
+            .0
+            .iter()
+            .position(|name| *name == class.name)
+            .unwrap_or(self.0.len()))
+    }
+
+    fn class(&self) -> Class {
+        type_switch_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("typeswitch:{}", self.0.join(",")).into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        let names = variant
+            .strip_prefix("typeswitch:")
+            .ok_or_else(|| VariantError::new(variant, "expected `typeswitch:` prefix"))?;
+        self.0 = names
+            .split(',')
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect();
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn branches(&self) -> u32 {
+        self.0.len() as u32 + 1
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::TestProgram;
+
+    /// Index of the branch taken for a value of a class
+    fn branch_of(class: &str, text: &str) -> String {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, class, text)
+            .node(2, "typeswitch", "typeswitch:number,string,bool")
+            .end(9, 1)
+            .chain(&[0, 1, 2])
+            .connect(1, 0, 2, 0);
+        for branch in 0..4 {
+            let id = 10 + branch as u32;
+            program
+                .edge(2, branch, id)
+                .value(id, "number", &branch.to_string())
+                .edge(id, 0, 9)
+                .connect(id, 0, 9, 0);
+        }
+        program.run_strings().remove(0)
+    }
+
+    #[test]
+    fn branch_by_class() {
+        assert_eq!(branch_of("number", "1"), "0");
+        assert_eq!(branch_of("string", "a"), "1");
+        assert_eq!(branch_of("bool", "true"), "2");
+        assert_eq!(branch_of("array", "[]"), "3");
+    }
+}
@@ -1,43 +1,87 @@
 use crate::{
     class::Class,
-    node::Node,
+    node::{Node, VariantError},
+    object::Object,
     socket::{InputSocket, OutputSocket},
-    ExecutionContext,
+    ExecutionContext, ExecutionError,
 };
 use std::{borrow::Cow, rc::Rc};
 
-use super::{any_class, string_class};
+use super::{
+    any_class, array_class, array_items, cached_class, dict_class, number_class, string_class,
+    Dict, NullType,
+};
 
 pub fn variable_get_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "variable_get".into(),
         nodes: vec![Rc::new(VariableGet) as Rc<dyn Node>],
         obj_from_str: None,
-    }
+    })
 }
 
 pub fn variable_set_class() -> Class {
-    Class {
+    cached_class(|| Class {
         name: "variable_set".into(),
-        nodes: vec![],
+        nodes: vec![Rc::new(VariableSet) as Rc<dyn Node>],
         obj_from_str: None,
-    }
+    })
+}
+
+pub fn variable_swap_class() -> Class {
+    cached_class(|| Class {
+        name: "variable_swap".into(),
+        nodes: vec![Rc::new(VariableSwap) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn variable_rotate_class() -> Class {
+    cached_class(|| Class {
+        name: "variable_rotate".into(),
+        nodes: vec![Rc::new(VariableRotate) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn accumulate_class() -> Class {
+    cached_class(|| Class {
+        name: "accumulate".into(),
+        nodes: vec![Rc::new(AccumulateNode { reset: false }) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn destructure_to_vars_class() -> Class {
+    cached_class(|| Class {
+        name: "destructure_to_vars".into(),
+        nodes: vec![Rc::new(DestructureToVarsNode(vec![])) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+pub fn dump_vars_class() -> Class {
+    cached_class(|| Class {
+        name: "dump_vars".into(),
+        nodes: vec![Rc::new(DumpVarsNode { all: false }) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+fn get_or_null(context: &ExecutionContext, name: &str) -> Rc<dyn Object> {
+    context
+        .get_variable(name)
+        .unwrap_or_else(|| Rc::new(NullType) as Rc<dyn Object>)
 }
 
 #[derive(Debug, Clone)]
 pub struct VariableGet;
 
 impl Node for VariableGet {
-    fn execute(&self, context: &mut ExecutionContext) -> usize {
-        let inputs = context.get_inputs();
-        context.set_outputs(
-            inputs
-                .get(0)
-                .and_then(|name| context.get_variable(&name.as_string()))
-                .into_iter()
-                .collect(),
-        );
-        0
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let name = context.get_string(0)?;
+        context.set_outputs(context.get_variable(&name).into_iter().collect());
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -57,6 +101,7 @@ impl Node for VariableGet {
     fn inputs(&self) -> Vec<InputSocket> {
         vec![InputSocket {
             class: string_class(),
+            default: None,
         }]
     }
 
@@ -73,12 +118,11 @@ impl Node for VariableGet {
 pub struct VariableSet;
 
 impl Node for VariableSet {
-    fn execute(&self, context: &mut ExecutionContext) -> usize {
-        let inputs = context.get_inputs();
-        let name = inputs[0].as_string();
-        let value = &inputs[1];
-        context.set_variable(&name, Rc::clone(value));
-        0
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let name = context.get_string(0)?;
+        let value = context.get_object(1)?;
+        context.set_variable(&name, value);
+        Ok(0)
     }
 
     fn class(&self) -> Class {
@@ -99,8 +143,59 @@ impl Node for VariableSet {
         vec![
             InputSocket {
                 class: string_class(),
+                default: None,
             },
-            InputSocket { class: any_class() },
+            InputSocket {
+                class: any_class(),
+                default: None,
+            },
+        ]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Exchange values of two variables. Missing variables are treated as null.
+#[derive(Debug, Clone)]
+pub struct VariableSwap;
+
+impl Node for VariableSwap {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let (name_a, name_b) = (context.get_string(0)?, context.get_string(1)?);
+        let value_a = get_or_null(context, &name_a);
+        let value_b = get_or_null(context, &name_b);
+        context.set_variable(&name_a, value_b);
+        context.set_variable(&name_b, value_a);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        variable_swap_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["swap".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "swap".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![
+            InputSocket {
+                class: string_class(),
+                default: None,
+            };
+            2
         ]
     }
 
@@ -112,3 +207,379 @@ impl Node for VariableSet {
         Rc::new(self.clone()) as Rc<dyn Node>
     }
 }
+
+/// Rotate values of variables named in the array: each variable gets the value of the previous
+/// one, the first gets the value of the last. Missing variables are treated as null.
+#[derive(Debug, Clone)]
+pub struct VariableRotate;
+
+impl Node for VariableRotate {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let names: Vec<String> = array_items(&context.get_object(0)?)
+            .iter()
+            .map(|name| name.as_string())
+            .collect();
+        let mut values: Vec<Rc<dyn Object>> = names
+            .iter()
+            .map(|name| get_or_null(context, name))
+            .collect();
+        values.rotate_right(1);
+        for (name, value) in names.iter().zip(values) {
+            context.set_variable(name, value);
+        }
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        variable_rotate_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["rotate".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "rotate".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Add a number to a running total kept in a variable and output the new total. A variable that
+/// is not set or doesn't hold a number counts as 0. The `reset` variant sets the total to 0.
+#[derive(Debug, Clone)]
+pub struct AccumulateNode {
+    reset: bool,
+}
+
+impl Node for AccumulateNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let name = context.get_string(0)?;
+        let total = if self.reset {
+            0.0
+        } else {
+            let current = context
+                .get_variable(&name)
+                .filter(|value| value.class() == number_class())
+                .map_or(0.0, |value| value.as_number());
+            current + context.get_number(1)?
+        };
+        let total = Rc::new(total) as Rc<dyn Object>;
+        context.set_variable(&name, Rc::clone(&total));
+        context.set_outputs(vec![total]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        accumulate_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["add".into(), "reset".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        if self.reset { "reset" } else { "add" }.into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.reset = match variant {
+            "add" => false,
+            "reset" => true,
+            _ => return Err(VariantError::new(variant, "expected `add` or `reset`")),
+        };
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        let name = InputSocket {
+            class: string_class(),
+            default: None,
+        };
+        if self.reset {
+            vec![name]
+        } else {
+            vec![
+                name,
+                InputSocket {
+                    class: number_class(),
+                    default: Some("1".into()),
+                },
+            ]
+        }
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: number_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Assign elements of an array to variables listed in the variant, in order. Variables without a
+/// matching element are set to null, extra elements are ignored.
+#[derive(Debug, Clone)]
+pub struct DestructureToVarsNode(Vec<String>);
+
+impl Node for DestructureToVarsNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let mut items = array_items(&context.get_object(0)?).into_iter();
+        for name in &self.0 {
+            let value = items
+                .next()
+                .unwrap_or_else(|| Rc::new(NullType) as Rc<dyn Object>);
+            context.set_variable(name, value);
+        }
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        destructure_to_vars_class()
+    }
+
+    /// Format: destructure:<name>,<name>,...
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec![self.current_variant()]
+    }
+
+    /// Format: destructure:<name>,<name>,...
+    fn current_variant(&self) -> Cow<'_, str> {
+        format!("destructure:{}", self.0.join(",")).into()
+    }
+
+    /// Format: destructure:<name>,<name>,...
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        let names = variant
+            .strip_prefix("destructure:")
+            .ok_or_else(|| VariantError::new(variant, "expected `destructure:` prefix"))?;
+        self.0 = names
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect();
+        Ok(())
+    }
+
+    fn accepts_arbitrary_variants(&self) -> bool {
+        true
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: array_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+/// Output a dict from names of variables to their values, for debugging and checkpointing. The
+/// `all` variant is meant to include variables of outer scopes, but variables are not scoped yet,
+/// so until they are it is the same as `dump_vars`.
+#[derive(Debug, Clone)]
+pub struct DumpVarsNode {
+    all: bool,
+}
+
+impl Node for DumpVarsNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let vars = Dict::from_iter(
+            context
+                .variables()
+                .into_iter()
+                .map(|(name, value)| (Rc::new(name) as Rc<dyn Object>, value)),
+        );
+        context.set_outputs(vec![Rc::new(vars) as Rc<dyn Object>]);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        dump_vars_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["dump_vars".into(), "all".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        if self.all { "all" } else { "dump_vars" }.into()
+    }
+
+    fn set_variant(&mut self, variant: &str) {
+        self.try_set_variant(variant).unwrap()
+    }
+
+    fn try_set_variant(&mut self, variant: &str) -> Result<(), VariantError> {
+        self.all = match variant {
+            "dump_vars" => false,
+            "all" => true,
+            _ => return Err(VariantError::new(variant, "expected `dump_vars` or `all`")),
+        };
+        Ok(())
+    }
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![OutputSocket {
+            class: dict_class(),
+        }]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{stdlib::dict_entries, test_utils::TestProgram};
+    use std::rc::Rc;
+
+    /// Set variables `a`, `b` and `c` to 1, 2 and 3, run the node under test at id 10 and end
+    /// with the values of the variables
+    fn with_abc(node: impl FnOnce(&mut TestProgram)) -> Vec<String> {
+        let mut program = TestProgram::new();
+        program.start(0);
+        for (id, name) in [(1, "a"), (2, "b"), (3, "c")] {
+            program
+                .node(id, "variable_set", "set")
+                .const_input(id, 0, name)
+                .const_input(id, 1, &id.to_string())
+                .node(id + 20, "variable_get", "get")
+                .const_input(id + 20, 0, name)
+                .connect(id + 20, 0, 30, id as usize - 1);
+        }
+        node(&mut program);
+        program.end(30, 3).chain(&[0, 1, 2, 3, 10, 21, 22, 23, 30]);
+        program.run_strings()
+    }
+
+    #[test]
+    fn swap_variables() {
+        let values = with_abc(|program| {
+            program
+                .node(10, "variable_swap", "swap")
+                .const_input(10, 0, "a")
+                .const_input(10, 1, "b");
+        });
+        assert_eq!(values, ["2", "1", "3"]);
+    }
+
+    #[test]
+    fn rotate_variables() {
+        let values = with_abc(|program| {
+            program
+                .node(10, "variable_rotate", "rotate")
+                .const_input(10, 0, r#"["a", "b", "c"]"#);
+        });
+        assert_eq!(values, ["3", "1", "2"]);
+    }
+
+    #[test]
+    fn accumulate_and_reset() {
+        let mut program = TestProgram::new();
+        program.start(0);
+        for (id, amount) in [(1, "3"), (2, "4"), (3, "5.5")] {
+            program
+                .node(id, "accumulate", "add")
+                .const_input(id, 0, "sum")
+                .const_input(id, 1, amount)
+                .connect(id, 0, 9, id as usize - 1);
+        }
+        program
+            .node(4, "accumulate", "reset")
+            .const_input(4, 0, "sum")
+            .connect(4, 0, 9, 3)
+            .node(5, "variable_get", "get")
+            .const_input(5, 0, "sum")
+            .connect(5, 0, 9, 4)
+            .end(9, 5)
+            .chain(&[0, 1, 2, 3, 4, 5, 9]);
+        assert_eq!(program.run_strings(), ["3", "7", "12.5", "0", "0"]);
+    }
+
+    #[test]
+    fn destructure_pads_with_null() {
+        let values = with_abc(|program| {
+            program
+                .node(10, "destructure_to_vars", "destructure:a,b,c")
+                .const_input(10, 0, "[1, 2]");
+        });
+        assert_eq!(values, ["1", "2", "null"]);
+    }
+
+    #[test]
+    fn destructure_ignores_extra_elements() {
+        let values = with_abc(|program| {
+            program
+                .node(10, "destructure_to_vars", "destructure:b")
+                .const_input(10, 0, "[5, 6]");
+        });
+        assert_eq!(values, ["1", "5", "3"]);
+    }
+
+    /// Set `name` and `count`, dump variables with a variant and read both back from the dict
+    fn dump_name_and_count(variant: &str) -> [String; 2] {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .node(1, "variable_set", "set")
+            .const_input(1, 0, "name")
+            .const_input(1, 1, "Ann")
+            .node(2, "variable_set", "set")
+            .const_input(2, 0, "count")
+            .const_input(2, 1, "3")
+            .node(3, "dump_vars", variant)
+            .end(9, 1)
+            .chain(&[0, 1, 2, 3, 9])
+            .connect(3, 0, 9, 0);
+        let dump = program.run().remove(0);
+        assert_eq!(dict_entries(&dump).len(), 2);
+        ["name", "count"].map(|name| dump.get_field(Rc::new(name.to_string())).to_string())
+    }
+
+    #[test]
+    fn dump_two_variables() {
+        for variant in ["dump_vars", "all"] {
+            assert_eq!(dump_name_and_count(variant), ["Ann", "3"]);
+        }
+    }
+}
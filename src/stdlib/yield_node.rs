@@ -0,0 +1,83 @@
+use super::{any_class, cached_class};
+use crate::{
+    class::Class,
+    node::Node,
+    socket::{InputSocket, OutputSocket},
+    ExecutionContext, ExecutionError,
+};
+use std::{borrow::Cow, rc::Rc};
+
+pub fn yield_node_class() -> Class {
+    cached_class(|| Class {
+        name: "yield".into(),
+        nodes: vec![Rc::new(YieldNode) as Rc<dyn Node>],
+        obj_from_str: None,
+    })
+}
+
+/// Pauses automatic execution and passes the input to the host, which can read it with
+/// `Executor::last_yield` and continue with `resume_auto`
+#[derive(Debug, Clone)]
+pub struct YieldNode;
+
+impl Node for YieldNode {
+    fn execute(&self, context: &mut ExecutionContext) -> Result<usize, ExecutionError> {
+        let value = context.get_object(0)?;
+        context.yield_value(value);
+        Ok(0)
+    }
+
+    fn class(&self) -> Class {
+        yield_node_class()
+    }
+
+    fn variants(&self) -> Vec<Cow<'_, str>> {
+        vec!["yield".into()]
+    }
+
+    fn current_variant(&self) -> Cow<'_, str> {
+        "yield".into()
+    }
+
+    fn set_variant(&mut self, _variant: &str) {}
+
+    fn inputs(&self) -> Vec<InputSocket> {
+        vec![InputSocket {
+            class: any_class(),
+            default: None,
+        }]
+    }
+
+    fn outputs(&self) -> Vec<OutputSocket> {
+        vec![]
+    }
+
+    fn clone_node(&self) -> Rc<dyn Node> {
+        Rc::new(self.clone()) as Rc<dyn Node>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::TestProgram;
+
+    #[test]
+    fn yield_and_resume() {
+        let mut program = TestProgram::new();
+        program
+            .start(0)
+            .value(1, "string", "halfway")
+            .node(2, "yield", "yield")
+            .value(3, "string", "done")
+            .end(9, 1)
+            .chain(&[0, 1, 2, 3, 9])
+            .connect(1, 0, 2, 0)
+            .connect(3, 0, 9, 0);
+        let mut executor = program.executor();
+        executor.start_execution(true).unwrap();
+        assert!(executor.final_output().is_none());
+        assert_eq!(executor.last_yield().unwrap().to_string(), "halfway");
+        executor.resume_auto().unwrap();
+        assert_eq!(executor.final_output().unwrap()[0].to_string(), "done");
+    }
+}
@@ -0,0 +1,181 @@
+//! Building and running small programs in tests
+
+use crate::{
+    module::ModulePath,
+    node::{NodeBranchId, NodeId, NodeInfo},
+    object::Object,
+    program::Program,
+    socket::{Connection, InputSocketId, OutputSocketId, SocketId},
+    stdlib::StdPlugin,
+    Executor,
+};
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+/// Path the main program is loaded at
+pub(crate) fn main_path() -> ModulePath {
+    ModulePath(vec![], "__main__".into())
+}
+
+/// Program put together node by node
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TestProgram(pub(crate) Program);
+
+impl TestProgram {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Place the default node of a `std` class
+    pub(crate) fn node(&mut self, id: NodeId, class: &str, variant: &str) -> &mut Self {
+        self.node_of(id, ModulePath(vec!["std".into()], class.into()), 0, variant)
+    }
+
+    pub(crate) fn node_of(
+        &mut self,
+        id: NodeId,
+        class: ModulePath,
+        idx: usize,
+        variant: &str,
+    ) -> &mut Self {
+        self.0.nodes.insert(
+            id,
+            NodeInfo {
+                class,
+                idx,
+                variant: variant.into(),
+            },
+        );
+        self
+    }
+
+    /// Start node named `main` with no outputs
+    pub(crate) fn start(&mut self, id: NodeId) -> &mut Self {
+        self.node(id, "start", "start#main#[]")
+    }
+
+    /// End node with an amount of `any` inputs
+    pub(crate) fn end(&mut self, id: NodeId, inputs: usize) -> &mut Self {
+        let inputs = vec!["\"any\""; inputs].join(",");
+        self.node(id, "end", &format!("end[{inputs}]"))
+    }
+
+    /// Node that outputs an object of a `std` class made from text, once it is executed
+    pub(crate) fn value(&mut self, id: NodeId, class: &str, text: &str) -> &mut Self {
+        self.node(id, "cast", &format!("cast:std.{class}"))
+            .const_input(id, 0, text)
+    }
+
+    pub(crate) fn edge(&mut self, from: NodeId, branch: usize, to: NodeId) -> &mut Self {
+        self.0.branch_edges.insert(NodeBranchId(from, branch), to);
+        self
+    }
+
+    /// Execute nodes one after another through their first branch
+    pub(crate) fn chain(&mut self, nodes: &[NodeId]) -> &mut Self {
+        for pair in nodes.windows(2) {
+            self.edge(pair[0], 0, pair[1]);
+        }
+        self
+    }
+
+    /// Pass an output of a node to an input of another node
+    pub(crate) fn connect(
+        &mut self,
+        from: NodeId,
+        output: usize,
+        to: NodeId,
+        input: usize,
+    ) -> &mut Self {
+        self.0.connections.insert(Connection {
+            output: OutputSocketId(SocketId(from, output)),
+            input: InputSocketId(SocketId(to, input)),
+        });
+        self
+    }
+
+    pub(crate) fn const_input(&mut self, node: NodeId, input: usize, value: &str) -> &mut Self {
+        self.0
+            .const_inputs
+            .insert(InputSocketId(SocketId(node, input)), value.into());
+        self
+    }
+
+    /// Executor with the standard library and this program loaded as the main program
+    pub(crate) fn executor(&self) -> Executor {
+        let mut executor = Executor::default();
+        executor.load_plugin(StdPlugin).unwrap();
+        executor.load_program(self.0.clone(), main_path()).unwrap();
+        executor
+    }
+
+    /// Run the program from its `main` start node, returning what its end node receives
+    pub(crate) fn run(&self) -> Vec<Rc<dyn Object>> {
+        let mut executor = self.executor();
+        executor.start_execution(true).unwrap();
+        executor.final_output().unwrap().to_vec()
+    }
+
+    /// Like `run`, but with the outputs in text form
+    pub(crate) fn run_strings(&self) -> Vec<String> {
+        self.run().iter().map(|value| value.to_string()).collect()
+    }
+}
+
+/// What a program ends with when a node takes its error branch in `run_node`
+const ERROR_BRANCH: &str = "<branch 1>";
+
+/// Run a single node of a `std` class on inputs given as pairs of class and text. Returns the
+/// outputs of the node if it continues through branch 0, `None` if it takes branch 1.
+pub(crate) fn run_node(
+    class: &str,
+    variant: &str,
+    inputs: &[(&str, &str)],
+    outputs: usize,
+) -> Option<Vec<String>> {
+    let mut program = TestProgram::new();
+    program.start(0);
+    let mut order = vec![0];
+    for (idx, (input_class, text)) in inputs.iter().enumerate() {
+        let id = idx as NodeId + 1;
+        program
+            .value(id, input_class, text)
+            .connect(id, 0, 100, idx);
+        order.push(id);
+    }
+    order.extend([100, 101]);
+    program
+        .node(100, class, variant)
+        .end(101, outputs)
+        .chain(&order);
+    for output in 0..outputs {
+        program.connect(100, output, 101, output);
+    }
+    program
+        .edge(100, 1, 102)
+        .value(102, "string", ERROR_BRANCH)
+        .end(103, 1)
+        .edge(102, 0, 103)
+        .connect(102, 0, 103, 0);
+    let result = program.run_strings();
+    (result != [ERROR_BRANCH]).then_some(result)
+}
+
+/// Output for an executor that can still be read after the executor took it
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedOutput(Rc<RefCell<Vec<u8>>>);
+
+impl SharedOutput {
+    pub(crate) fn text(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+//! Running programs with the executor binary
+
+use std::{
+    fs,
+    path::PathBuf,
+    process::{Command, Output},
+};
+
+fn ssce(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_ssce"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .unwrap()
+}
+
+/// Write a program in RON format to a temporary file
+fn write_program(name: &str, program: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("ssce-test-{}-{name}.ron.ssc", std::process::id()));
+    fs::write(&path, program).unwrap();
+    path
+}
+
+#[test]
+fn hello_world_in_every_format() {
+    for file in [
+        "example_programs/hello_world.ron.ssc",
+        "example_programs/hello_world.json.ssc",
+        "example_programs/hello_world.bin.ssc",
+    ] {
+        let output = ssce(&[file]);
+        assert!(output.status.success(), "{file}: {output:?}");
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "Hello, world!\n");
+    }
+}
+
+#[test]
+fn number_passed_to_end_is_exit_code() {
+    // Node 1 casts the const "3" to a number, node 2 ends the program with it
+    let path = write_program(
+        "exit-code",
+        r#"(imports:None,nodes:{0:(class:["std","start"],idx:0,variant:"start#main#[]"),1:(class:["std","cast"],idx:0,variant:"cast:std.number"),2:(class:["std","end"],idx:0,variant:"end[\"any\"]")},node_positions:None,classes:[],branch_edges:{0:1,4294967296:2},connections:[(output:(4294967296),input:(8589934592))],const_inputs:{(4294967296):"3"})"#,
+    );
+    let output = ssce(&[path.to_str().unwrap()]);
+    fs::remove_file(path).unwrap();
+    assert_eq!(output.status.code(), Some(3), "{output:?}");
+}
+
+#[test]
+fn entry_point_is_chosen_by_name() {
+    // Start node `main` prints "main", start node `test` prints "test"
+    let path = write_program(
+        "entry",
+        r#"(imports:None,nodes:{0:(class:["std","start"],idx:0,variant:"start#main#[]"),1:(class:["std","print"],idx:0,variant:"println"),2:(class:["std","start"],idx:0,variant:"start#test#[]"),3:(class:["std","print"],idx:0,variant:"println")},node_positions:None,classes:[],branch_edges:{0:1,8589934592:3},connections:[],const_inputs:{(4294967296):"main",(12884901888):"test"})"#,
+    );
+    let path = path.to_str().unwrap();
+    let main = ssce(&[path]);
+    let test = ssce(&[path, "--entry", "test"]);
+    let missing = ssce(&[path, "--entry", "setup"]);
+    fs::remove_file(path).unwrap();
+    assert_eq!(String::from_utf8_lossy(&main.stdout), "main\n");
+    assert_eq!(String::from_utf8_lossy(&test.stdout), "test\n");
+    assert_eq!(missing.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("\"setup\""));
+}
+
+#[test]
+fn malformed_program_reports_path_and_position() {
+    let path = write_program("malformed", "(imports:None,\nnodes:{0:oops})");
+    let output = ssce(&[path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(output.status.code(), Some(1), "{output:?}");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let expected = format!("error in {} at line 2: ", path.display());
+    assert!(stderr.starts_with(&expected), "{stderr}");
+    assert!(!stderr.contains("panicked"), "{stderr}");
+}